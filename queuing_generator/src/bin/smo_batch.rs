@@ -0,0 +1,154 @@
+//! Пакетный запуск эмуляции системы массового обслуживания без графического интерфейса
+//!
+//! ```text
+//! smo_batch <options.json> [--every <rounds>] [--out <file>] [--wait-stats <file>]
+//! smo_batch <options.json> --check-rng <size>
+//! ```
+//!
+//! Настройки читаются из json файла в том же формате, что отправляет графический интерфейс.
+//! Эмуляция выполняется без пауз, статистика пишется построчно в формате JSON Lines:
+//! каждые `--every` раундов промежуточная, в конце - итоговая.
+//! С `--wait-stats` в отдельный файл пишется статистика времени ожидания задач
+//! (мат. ожидание, дисперсия, гистограмма и тп).
+//!
+//! С `--check-rng` эмуляция не выполняется, вместо этого генераторы интервала появления и длительности
+//! задач проверяются по критерию Колмогорова-Смирнова на соответствие заданному закону
+//! (для постоянного времени и эмпирического закона критерий не применим).
+
+use anyhow::{anyhow, bail, Context, Result};
+use queuing_generator::smo_engine::engine::Engine;
+use queuing_generator::smo_engine::model::{Options, Stats};
+use serde::Serialize;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::str::FromStr;
+use utils::criteria::KolmogorovSmirnov;
+use utils::StatsOptions;
+
+const USAGE: &str = "Usage:
+    smo_batch <options.json> [--every <rounds>] [--out <file>] [--wait-stats <file>]
+    smo_batch <options.json> --check-rng <size>";
+
+/// Разобранные аргументы командной строки
+struct BatchCmd {
+    options_path: String,
+    every: Option<usize>,
+    out: Option<String>,
+    wait_stats: Option<String>,
+    check_rng: Option<usize>,
+}
+
+/// Результат проверки генераторов эмуляции
+#[derive(Serialize)]
+struct RngCheck {
+    /// None, если закон дискретный и критерий не применим
+    appearance_time: Option<KolmogorovSmirnov>,
+    task_weight_time: Option<KolmogorovSmirnov>,
+}
+
+fn main() -> Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let BatchCmd {
+        options_path,
+        every,
+        out,
+        wait_stats,
+        check_rng,
+    } = parse_args(&args).map_err(|err| anyhow!("{}\n\n{}", err, USAGE))?;
+
+    let json = fs::read_to_string(&options_path)
+        .with_context(|| format!("Can't read options from {:?}", options_path))?;
+    let options = serde_json::from_str::<Options>(&json)
+        .with_context(|| format!("Can't parse options from {:?}", options_path))?
+        .load()?;
+
+    let mut writer: Box<dyn Write> = match out {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(&path).with_context(|| format!("Can't create {:?}", path))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout())),
+    };
+
+    let mut engine = Engine::new(options)?;
+
+    if let Some(size) = check_rng {
+        let (appearance_time, task_weight_time) = engine.check_rng(size)?;
+        let check = RngCheck {
+            appearance_time,
+            task_weight_time,
+        };
+        serde_json::to_writer_pretty(&mut writer, &check)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        return Ok(());
+    }
+
+    let stats = engine.run_to_end(every, |stats| write_stats(&mut writer, stats))?;
+    write_stats(&mut writer, &stats)?;
+    writer.flush()?;
+
+    if let Some(path) = wait_stats {
+        let file = File::create(&path).with_context(|| format!("Can't create {:?}", path))?;
+        let stats = engine.wait_time_stats(&StatsOptions::default())?;
+        serde_json::to_writer_pretty(BufWriter::new(file), &stats)?;
+    }
+
+    Ok(())
+}
+
+/// Пишет статистику одной строкой json
+fn write_stats(writer: &mut dyn Write, stats: &Stats) -> Result<()> {
+    serde_json::to_writer(&mut *writer, stats)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Разбирает аргументы командной строки
+fn parse_args(args: &[String]) -> Result<BatchCmd> {
+    let options_path = match args.first() {
+        Some(path) if !path.starts_with("--") => path.clone(),
+        _ => bail!("Expected path to options json"),
+    };
+
+    let mut every = None;
+    let mut out = None;
+    let mut wait_stats = None;
+    let mut check_rng = None;
+
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        let value = rest
+            .next()
+            .ok_or_else(|| anyhow!("Option {} requires a value", arg))?;
+        match arg.as_str() {
+            "--every" => {
+                let rounds = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad number of rounds {:?}: {}", value, err))?;
+                if rounds == 0 {
+                    bail!("Number of rounds should be positive");
+                }
+                every = Some(rounds)
+            }
+            "--out" => out = Some(value.clone()),
+            "--wait-stats" => wait_stats = Some(value.clone()),
+            "--check-rng" => {
+                let size = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad sample size {:?}: {}", value, err))?;
+                if size == 0 {
+                    bail!("Sample size should be positive");
+                }
+                check_rng = Some(size)
+            }
+            _ => bail!("Unknown option {:?}", arg),
+        }
+    }
+
+    Ok(BatchCmd {
+        options_path,
+        every,
+        out,
+        wait_stats,
+        check_rng,
+    })
+}
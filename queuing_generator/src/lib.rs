@@ -0,0 +1,7 @@
+//! Модель системы массового обслуживания.
+//!
+//! Библиотека не зависит от web-view, графический интерфейс собирается с фичей `gui`.
+
+pub mod smo_engine;
+
+pub use utils;
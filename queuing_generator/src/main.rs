@@ -3,13 +3,12 @@ extern crate web_view;
 use anyhow::Result;
 pub use serde::{Deserialize, Serialize};
 
-use crate::smo_engine::engine::Engine;
-use crate::smo_engine::model::Options;
+use queuing_generator::smo_engine::engine::Engine;
+use queuing_generator::smo_engine::model::Options;
 use std::sync::{Arc, Mutex};
+use utils::generator;
 use web_view::*;
 
-mod smo_engine;
-
 fn main() {
     let _ = web_view::builder()
         .title("Модель системы массового обслуживания")
@@ -26,7 +25,7 @@ fn main() {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "type")]
 pub enum Action {
-    Start { options: Options },
+    Start { options: Box<Options> },
     Stop,
     Stats,
 }
@@ -38,34 +37,63 @@ fn parse_cmd(arg: &str) -> Result<Action> {
 }
 
 fn invoke_handler(wv: &mut WebView<Option<Arc<Mutex<Engine>>>>, arg: &str) -> WVResult {
-    let action = parse_cmd(arg).expect("Cmd should be defined");
+    let action = match parse_cmd(arg) {
+        Ok(action) => action,
+        Err(err) => {
+            println!("Bad cmd {:?}: {}", arg, err);
+            return Ok(());
+        }
+    };
 
     match action {
         Action::Start { options } => {
-            let engine = Arc::new(Mutex::new(Engine::new(options)));
+            // читаем эмпирические законы из файлов и проверяем параметры
+            let options = match options.load() {
+                Ok(options) => options,
+                Err(err) => {
+                    println!("{:#}", err);
+                    return Ok(());
+                }
+            };
+            // страница показывает начальное значение числом JavaScript, большее значение исказится
+            if matches!(options.seed, Some(seed) if seed > generator::MAX_SEED) {
+                println!("Seed should not exceed {}", generator::MAX_SEED);
+                return Ok(());
+            }
+            let time_scale_millis = options.time_scale_millis;
+            let engine = match Engine::new(options) {
+                Ok(engine) => Arc::new(Mutex::new(engine)),
+                Err(err) => {
+                    println!("{:#}", err);
+                    return Ok(());
+                }
+            };
 
             // запускаем эмуляцию в отдельном треде
-            Engine::start(engine.clone(), options.time_scale_millis)
-                .expect("Не смог начать симуляцию");
+            if let Err(err) = Engine::start(engine.clone(), time_scale_millis) {
+                println!("Не смог начать симуляцию: {:#}", err);
+                return Ok(());
+            }
 
             // перетираем прошлый движок во внутреннем состоянии программы
             wv.user_data_mut().replace(engine);
 
-            let start_js = format!("started(true)");
+            let start_js = "started(true)";
             println!("start_js: {:?}", start_js);
 
             // вызываем функцию в Js для отрисовки UI
-            wv.eval(&start_js)?;
+            wv.eval(start_js)?;
         }
         Action::Stop => {
             // останавливаем эмуляцию
-            let data = wv.user_data().clone();
-            data.map(|arc| Engine::stop(arc));
+            if let Some(engine) = wv.user_data().clone() {
+                Engine::stop(engine);
+            }
 
-            let stop_js = format!("started(false)");
+            let stop_js = "started(false)";
             println!("stop_js: {:?}", stop_js);
             // вызываем функцию в Js для отрисовки UI
-            wv.eval(&stop_js)?;
+            wv.eval(stop_js)?;
         }
         Action::Stats => {
             // получение статистики
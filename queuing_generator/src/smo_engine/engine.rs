@@ -1,7 +1,11 @@
 //! Реализация движока системы массивого обслуживания
 
-use crate::smo_engine::model::{Options, State, Stats, Task};
+use crate::smo_engine::model::{Options, Overflow, State, Stats, Task};
 use anyhow::Result;
+use utils::criteria::KolmogorovSmirnov;
+use utils::generator;
+use utils::generator::Generator;
+use utils::StatsOptions;
 
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -12,12 +16,25 @@ use std::time::Duration;
 pub struct Engine {
     state: State,
     options: Options,
+    /// Генератор случайных чисел, выбранный пользователем
+    rng: Box<dyn Generator + Send>,
 }
 
 impl Engine {
-    pub fn new(options: Options) -> Self {
-        let state = State::new();
-        Engine { state, options }
+    /// Создает движок, вернет ошибку для недопустимых параметров системы или генератора,
+    /// см. [Options::validate]
+    pub fn new(options: Options) -> Result<Self> {
+        options.validate()?;
+        let seed = generator::resolve_seed(options.seed);
+        let mut state = State::new(seed, &options);
+        let mut rng = options.generator.create(seed)?;
+        // первая задача появляется через случайный интервал от начала эмуляции
+        state.next_arrival = options.appearance_time.sample(rng.as_mut());
+        Ok(Engine {
+            state,
+            options,
+            rng,
+        })
     }
 
     /// Начинает эмуляцию в фоне
@@ -55,13 +72,51 @@ impl Engine {
 
     /// Останавливает эмуляцию в фоне
     pub fn stop(engine: Arc<Mutex<Self>>) {
-        println!("Start engine");
+        println!("Stop engine");
         engine
             .lock()
             .map(|mut e| e.state.started = false)
             .expect("Не смог захватить мьютекс");
     }
 
+    /// Проверяет по критерию Колмогорова-Смирнова генераторы интервала появления и длительности задач
+    /// на выборках размера `size`, возвращает результаты для обоих генераторов.
+    /// Для дискретных законов критерий не применим, вместо результата будет None.
+    /// Выборки берутся из отдельного подпотока, поэтому проверка не меняет ход эмуляции
+    pub fn check_rng(
+        &self,
+        size: usize,
+    ) -> Result<(Option<KolmogorovSmirnov>, Option<KolmogorovSmirnov>)> {
+        let seed = generator::substream_seed(self.state.seed, 1);
+        let mut rng = self.options.generator.create(seed)?;
+        let appearance = self.options.appearance_time.check(rng.as_mut(), size);
+        let task_weight = self.options.task_weight_time.check(rng.as_mut(), size);
+
+        Ok((appearance, task_weight))
+    }
+
+    /// Выполняет эмуляцию до конца без пауз и возвращает итоговую статистику.
+    /// Если задан `every`, то каждые `every` раундов промежуточная статистика передается в `on_stats`
+    pub fn run_to_end<F>(&mut self, every: Option<usize>, mut on_stats: F) -> Result<Stats>
+    where
+        F: FnMut(&Stats) -> Result<()>,
+    {
+        self.state.started = true;
+
+        while !self.time_is_over() {
+            let now = self.state.now + 1;
+            self.make_round(now);
+
+            match every {
+                Some(every) if every > 0 && now % every == 0 => on_stats(&self.get_stats())?,
+                _ => {}
+            }
+        }
+
+        self.state.started = false;
+        Ok(self.get_stats())
+    }
+
     /// Рассчитывает модель для заданного момента времени
     pub fn make_round(&mut self, now: usize) {
         // println!("Раунд: {:?}, state: {:?}", now, self.state);
@@ -72,79 +127,153 @@ impl Engine {
         // обновляем часы
         self.state.now = now;
 
-        // пробуем сосздать задачу, если создана кладем в очередь согласно приоритету
-        self.put_task(Task::try_new(now, time_elapsed, self.options));
+        // сначала завершаем задачи и освобождаем места в очереди
+        self.serve(time_elapsed);
 
-        let rest_work = self.state.rest_time_working;
+        // все задачи, появившиеся к текущему моменту, кладем в очередь согласно приоритету
+        while self.state.next_arrival <= now as f64 {
+            let task = Task::new(now, &self.options, self.rng.as_mut());
+            self.put_task(task);
+            self.state.next_arrival += self.options.appearance_time.sample(self.rng.as_mut());
+        }
+
+        // задачи, появившиеся в этом раунде, сразу занимают свободные приборы
+        self.serve(0);
+    }
+
+    /// Продвигает обслуживание на `time_elapsed` и запускает задачи на свободных приборах
+    /// в порядке их номеров
+    fn serve(&mut self, time_elapsed: usize) {
+        for idx in 0..self.state.servers.len() {
+            let server = &mut self.state.servers[idx];
+            let rest_work = server.rest_time_working;
+
+            if rest_work > 0 {
+                // задача работает, обновляем остаток времени
+                let worked = rest_work.min(time_elapsed);
+                server.rest_time_working = rest_work - worked;
+                server.busy_time += worked;
+                self.state.load += worked;
+            }
 
-        if rest_work == 0 {
-            // запущенных задач нет, запускаем новую если есть
-            self.state.task = None;
-            self.try_start_task();
-        } else {
-            // задача пока работает, обновляем остаток времени
-            self.state.rest_time_working = rest_work - time_elapsed as u32;
-            self.state.load += 1;
+            if self.state.servers[idx].rest_time_working == 0 {
+                // прибор свободен, запускаем новую задачу если есть.
+                // Задачи нулевой длительности завершаются сразу, следующая запускается в том же раунде
+                self.finish_task(idx);
+                while self.try_start_task(idx) && self.state.servers[idx].rest_time_working == 0 {
+                    self.finish_task(idx);
+                }
+            }
         }
     }
 
-    /// Складываем задачу в очередь
-    pub fn put_task(&mut self, task: Option<Task>) {
-        task.map(|task| {
-            println!("push task to queue {:?}", task);
+    /// Складываем задачу в очередь ее класса приоритета. Если для нее нет места, поступаем согласно [Overflow]
+    pub fn put_task(&mut self, task: Task) {
+        // println!("push task to queue {:?}", task);
+
+        let priority = task.priority;
+        self.state.classes[priority].task_arrived_total += 1;
+
+        let own = self.state.queues[priority].len();
+        let own_full = self.options.classes[priority]
+            .capacity
+            .map_or(false, |c| own >= c);
+        let total_full = self
+            .options
+            .capacity
+            .map_or(false, |c| self.state.task_in_q() >= c);
 
-            if task.low_priority {
-                self.state.low_prior_queue.push(task) // LIFO
-            } else {
-                self.state.queue.push_back(task) // FIFO
+        if !own_full && !total_full {
+            self.state.queues[priority].push(task);
+            return;
+        }
+
+        self.state.task_blocked_total += 1;
+        let dropped = match self.options.overflow {
+            Overflow::Reject => None,
+            // освобождаем место в своей очереди, а если переполнена только общая вместимость
+            // и своя очередь пуста - в очереди самого низкого приоритета
+            Overflow::DropOldest if own > 0 => self.state.queues[priority].pop_oldest(),
+            Overflow::DropOldest if !own_full => self
+                .lowest_queue(0)
+                .and_then(|idx| self.state.queues[idx].pop_oldest()),
+            Overflow::DropOldest => None,
+            Overflow::PushOut if !own_full => self
+                .lowest_queue(priority + 1)
+                .and_then(|idx| self.state.queues[idx].pop_newest()),
+            Overflow::PushOut => None,
+        };
+
+        match dropped {
+            Some(dropped) => {
+                self.state.classes[dropped.priority].task_lost_total += 1;
+                self.state.queues[priority].push(task);
             }
-        });
+            None => self.state.classes[priority].task_lost_total += 1,
+        }
     }
 
-    /// Запускаем задачу на выполнение если что то есть в очереди и возвращает эту задачу.
-    /// Сначала пытаемся достать из очереди с нормальным приоритетом, затем из очереди с низким.
-    pub fn try_start_task(&mut self) {
-        self.state
-            .queue
-            .pop_front() // FIFO
-            .or(self.state.low_prior_queue.pop()) // LIFO
-            .map(|task| self.update_state(task))
-            .or_else(|| {
-                println!("Обе очерели пусты, нечего запускать");
-                None
-            });
+    /// Номер непустой очереди самого низкого приоритета среди классов, начиная с `from`
+    fn lowest_queue(&self, from: usize) -> Option<usize> {
+        (from..self.state.queues.len())
+            .rev()
+            .find(|idx| !self.state.queues[*idx].is_empty())
+    }
+
+    /// Запускаем задачу на приборе `server` если что то есть в очереди, вернет true если задача запущена.
+    /// Задача берется из непустой очереди самого высокого приоритета.
+    pub fn try_start_task(&mut self, server: usize) -> bool {
+        let rng = self.rng.as_mut();
+        let task = self
+            .state
+            .queues
+            .iter_mut()
+            .find_map(|queue| queue.pop(rng));
+
+        // если все очереди пусты, запускать нечего
+        match task {
+            Some(task) => {
+                self.update_state(task, server);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Обновляем внутренне состояние системы
-    fn update_state(&mut self, task: Task) {
-        self.state.load += 1;
+    /// Освобождает прибор `server`, если на нем была задача - она считается обслуженной
+    fn finish_task(&mut self, server: usize) {
+        let server = &mut self.state.servers[server];
+        if server.task.take().is_some() {
+            server.task_served_total += 1;
+        }
+    }
 
-        self.state.rest_time_working = task.require_time as u32;
+    /// Обновляем внутренне состояние системы при запуске задачи на приборе `server`
+    fn update_state(&mut self, task: Task, server: usize) {
+        self.state.servers[server].rest_time_working = task.require_time;
 
         if self.state.min_task_time_require > task.require_time {
             self.state.min_task_time_require = task.require_time
         }
 
+        if self.options.deadline_time.is_some()
+            && self.state.now + task.require_time > task.deadline
+        {
+            self.state.deadline_missed_total += 1;
+        }
+
         let wait_time = self.state.now - task.incoming_time;
-        self.state.task_done_total += 1;
-        self.state.task_wait_time_total += wait_time;
-
-        if task.low_priority {
-            self.state.low_prior_task_done_total += 1;
-            self.state.low_prior_task_wait_time_total += wait_time;
-
-            if self.state.low_prior_task_max_wait_time_total < wait_time {
-                self.state.low_prior_task_max_wait_time_total = wait_time;
-            };
-        } else {
-            if self.state.normal_prior_task_max_wait_time_total < wait_time {
-                self.state.normal_prior_task_max_wait_time_total = wait_time;
-            };
+        self.state.wait_times.push(wait_time);
+
+        let class = &mut self.state.classes[task.priority];
+        class.task_done_total += 1;
+        class.task_wait_time_total += wait_time;
+        if class.task_max_wait_time < wait_time {
+            class.task_max_wait_time = wait_time;
         }
 
-        self.state.task_wait_in_q_total +=
-            self.state.queue.len() + self.state.low_prior_queue.len();
-        self.state.task.replace(task);
+        self.state.task_wait_in_q_total += self.state.task_in_q();
+        self.state.servers[server].task.replace(task);
     }
 
     /// Вернет true если время эмуляции вышло
@@ -156,4 +285,191 @@ impl Engine {
     pub fn get_stats(&self) -> Stats {
         self.state.get_stats()
     }
+
+    /// Считает стохастические величины (мат. ожидание, дисперсию, гистограмму и тп)
+    /// для времени ожидания запущенных задач
+    pub fn wait_time_stats(&self, options: &StatsOptions) -> Result<utils::Stats> {
+        Ok(utils::stats(&self.state.wait_times, options)?.with_seed(self.state.seed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::smo_engine::model::{ClassStats, Distribution, PriorityClass};
+    use crate::smo_engine::queue::Discipline;
+
+    const ROUNDS: usize = 200_000;
+
+    /// Настройки системы M/M/c с интенсивностями появления `arrival` и обслуживания `service`
+    fn mmc(servers: usize, arrival: f64, service: f64) -> Options {
+        Options {
+            appearance_time: Distribution::Exponential { rate: arrival },
+            task_weight_time: Distribution::Exponential { rate: service },
+            classes: vec![class(1.)],
+            servers,
+            capacity: None,
+            overflow: Default::default(),
+            deadline_time: None,
+            time_scale_millis: 0,
+            max_number_of_rounds: ROUNDS,
+            generator: Default::default(),
+            seed: Some(3),
+        }
+    }
+
+    fn class(probability: f64) -> PriorityClass {
+        PriorityClass {
+            probability,
+            discipline: Discipline::Fifo,
+            capacity: None,
+        }
+    }
+
+    fn run(options: Options) -> Stats {
+        let mut engine = Engine::new(options.load().unwrap()).unwrap();
+        engine.run_to_end(None, |_| Ok(())).unwrap()
+    }
+
+    /// Система M/M/1 с интенсивностями λ = 0.2 и μ = 0.4: загрузка ρ = λ / μ = 0.5,
+    /// среднее время ожидания в очереди ρ / (μ - λ) = 2.5
+    #[test]
+    fn mm1_matches_theory() {
+        let stats = run(mmc(1, 0.2, 0.4));
+
+        assert_eq!(stats.servers.len(), 1);
+        assert!((stats.utilization - 0.5).abs() < 0.02, "{:?}", stats);
+        assert!((stats.avg_task_wait_time - 2.5).abs() < 0.3, "{:?}", stats);
+    }
+
+    /// Система M/M/2 с интенсивностями λ = 0.5 и μ = 0.4: загрузка ρ = λ / (2μ) = 0.625,
+    /// по формуле Эрланга C вероятность ожидания 0.481, среднее время ожидания 0.481 / (2μ - λ) = 1.6
+    #[test]
+    fn mm2_matches_theory() {
+        let stats = run(mmc(2, 0.5, 0.4));
+
+        assert_eq!(stats.servers.len(), 2);
+        assert!((stats.utilization - 0.625).abs() < 0.02, "{:?}", stats);
+        assert!((stats.throughput - 0.5).abs() < 0.02, "{:?}", stats);
+        assert!((stats.avg_task_wait_time - 1.6).abs() < 0.3, "{:?}", stats);
+
+        let busy = stats.servers.iter().map(|s| s.utilization).sum::<f32>() / 2.;
+        assert!((busy - stats.utilization).abs() < 1e-3, "{:?}", stats);
+    }
+
+    /// Система M/M/1/5 с λ = 0.3 и μ = 0.4 (в очереди не больше 4 задач): при ρ = 0.75
+    /// вероятность блокировки (1 - ρ) ρ^5 / (1 - ρ^6) = 0.072
+    #[test]
+    fn mm1k_blocking_matches_theory() {
+        let mut options = mmc(1, 0.3, 0.4);
+        options.capacity = Some(4);
+        let stats = run(options);
+
+        assert!(stats.task_in_q_total <= 4, "{:?}", stats);
+        assert!(
+            (stats.blocking_probability - 0.072).abs() < 0.01,
+            "{:?}",
+            stats
+        );
+        assert_eq!(stats.blocking_probability, stats.loss_rate);
+        assert_eq!(
+            stats.task_arrived_total,
+            stats.task_done_total + stats.task_lost_total + stats.task_in_q_total
+        );
+    }
+
+    /// Задача низкого приоритета не теряется, если есть задачи с более высоким приоритетом
+    #[test]
+    fn no_task_is_lost() {
+        let mut options = mmc(1, 0.3, 0.4);
+        options.classes = vec![class(0.5), class(0.5)];
+        options.max_number_of_rounds = 20_000;
+        let stats = run(options);
+
+        assert_eq!(stats.task_lost_total, 0);
+        assert_eq!(
+            stats.task_arrived_total,
+            stats.task_done_total + stats.task_in_q_total
+        );
+    }
+
+    /// Первой самой короткой задачи уменьшает среднее время ожидания, первой самой длинной - увеличивает
+    #[test]
+    fn sjf_reduces_wait_time() {
+        let wait = |discipline| {
+            let mut options = mmc(1, 0.35, 0.4);
+            options.classes[0].discipline = discipline;
+            run(options).avg_task_wait_time
+        };
+
+        let (fifo, sjf, ljf) = (
+            wait(Discipline::Fifo),
+            wait(Discipline::Sjf),
+            wait(Discipline::Ljf),
+        );
+        assert!(sjf < fifo && fifo < ljf, "{} {} {}", sjf, fifo, ljf);
+    }
+
+    /// Задачи более высокого приоритета ждут меньше, а показатели классов в сумме дают общие
+    #[test]
+    fn classes_are_served_by_priority() {
+        let mut options = mmc(1, 0.3, 0.4);
+        options.classes = vec![class(0.2), class(0.3), class(0.5)];
+        let stats = run(options);
+
+        assert_eq!(stats.classes.len(), 3);
+        let wait: Vec<_> = stats.classes.iter().map(|c| c.avg_task_wait_time).collect();
+        assert!(wait[0] < wait[1] && wait[1] < wait[2], "{:?}", wait);
+
+        let sum = |f: fn(&ClassStats) -> usize| stats.classes.iter().map(f).sum::<usize>();
+        assert_eq!(sum(|c| c.task_arrived_total), stats.task_arrived_total);
+        assert_eq!(sum(|c| c.task_done_total), stats.task_done_total);
+        assert_eq!(sum(|c| c.task_in_q_total), stats.task_in_q_total);
+    }
+
+    /// Начальное значение из часов, прочитанное страницей как число JavaScript (f64),
+    /// воспроизводит эмуляцию
+    #[test]
+    fn clock_seed_replays_run() {
+        let mut options = mmc(1, 0.3, 0.4);
+        options.seed = None;
+        options.max_number_of_rounds = 5_000;
+        let first = serde_json::to_value(run(options.clone())).unwrap();
+
+        let seed = first["seed"].as_f64().unwrap() as u64;
+        assert!(seed <= generator::MAX_SEED);
+
+        options.seed = Some(seed);
+        let second = serde_json::to_value(run(options)).unwrap();
+        assert_eq!(first, second);
+    }
+
+    /// Движок проверяет настройки сам, без [Options::load]: при нулевом интервале появления
+    /// эмуляция зациклилась бы в первом раунде
+    #[test]
+    fn new_validates_options() {
+        let mut options = mmc(1, 0.3, 0.4);
+        options.appearance_time = Distribution::Deterministic { value: 0. };
+        assert!(Engine::new(options).is_err());
+
+        let mut options = mmc(1, 0.3, 0.4);
+        options.servers = 0;
+        assert!(Engine::new(options).is_err());
+
+        assert!(Engine::new(mmc(1, 0.3, 0.4)).is_ok());
+    }
+
+    /// Проверка генераторов не меняет результат эмуляции с тем же начальным значением
+    #[test]
+    fn check_rng_keeps_run() {
+        let options = mmc(1, 0.3, 0.4).load().unwrap();
+        let plain = serde_json::to_value(run(options.clone())).unwrap();
+
+        let mut engine = Engine::new(options).unwrap();
+        let (appearance, task_weight) = engine.check_rng(1_000).unwrap();
+        assert!(appearance.is_some() && task_weight.is_some());
+        let checked = serde_json::to_value(engine.run_to_end(None, |_| Ok(())).unwrap()).unwrap();
+
+        assert_eq!(plain, checked);
+    }
 }
@@ -4,5 +4,7 @@
 pub mod engine;
 /// Описание моделей системы
 pub mod model;
+/// Дисциплины обслуживания очередей
+pub mod queue;
 /// Методы генерации случайных величин
-mod rng;
+pub mod rng;
@@ -1,31 +1,293 @@
+use crate::smo_engine::queue::{Discipline, Queue};
 use crate::smo_engine::rng;
-use crate::smo_engine::rng::next_int;
+use crate::smo_engine::rng::Truncation;
+use anyhow::{anyhow, bail, Context, Result};
 pub use serde::{Deserialize, Serialize};
 use std::cmp::max;
-use std::collections::VecDeque;
-
-/// Настройки для нормального распределения
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct Distribution {
-    /// Матожидание для интервала появления
-    pub expectation_time: usize,
-    /// Дисперсия для интервала появления
-    pub dispersion_time: usize,
+use std::fs;
+use std::str::FromStr;
+use utils::criteria::KolmogorovSmirnov;
+use utils::distribution::{self, Distribution as Law};
+use utils::generator::{Generator, GeneratorKind};
+
+/// Закон распределения случайного времени (интервала между задачами или длительности обслуживания)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum Distribution {
+    /// Постоянное время value
+    Deterministic { value: f64 },
+    /// Равномерный закон на [low, high)
+    Uniform { low: f64, high: f64 },
+    /// Показательный закон с интенсивностью rate (мат. ожидание 1 / rate)
+    Exponential { rate: f64 },
+    /// Нормальный закон с мат. ожиданием mean и среднеквадратичным отклонением deviation,
+    /// отрицательные значения обрабатываются согласно truncation
+    Normal {
+        mean: f64,
+        deviation: f64,
+        #[serde(default)]
+        truncation: Truncation,
+    },
+    /// Закон Эрланга: сумма shape показательных величин с интенсивностью rate
+    Erlang { shape: u32, rate: f64 },
+    /// Гиперэкспоненциальный закон: с вероятностью probabilities\[i\] показательная величина
+    /// с интенсивностью rates\[i\]
+    Hyperexponential {
+        probabilities: Vec<f64>,
+        rates: Vec<f64>,
+    },
+    /// Равновероятный выбор одного из наблюдавшихся значений. Значения задаются в `values`
+    /// или читаются из файла `file` (числа через пробел, запятую или с новой строки), см. [Options::load]
+    Empirical {
+        #[serde(default)]
+        values: Vec<f64>,
+        #[serde(default)]
+        file: Option<String>,
+    },
+}
+
+impl Distribution {
+    /// Читает значения эмпирического закона из файла, если он задан
+    pub fn load(&mut self) -> Result<()> {
+        if let Distribution::Empirical {
+            values,
+            file: Some(file),
+        } = self
+        {
+            let text = fs::read_to_string(&*file)
+                .with_context(|| format!("Can't read empirical values from {:?}", file))?;
+            *values = text
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .filter(|value| !value.is_empty())
+                .map(|value| {
+                    f64::from_str(value)
+                        .map_err(|err| anyhow!("Bad value {:?} in {:?}: {}", value, file, err))
+                })
+                .collect::<Result<_>>()?;
+        }
+        Ok(())
+    }
+
+    /// Проверяет параметры закона: время не может быть отрицательным
+    /// (кроме нормального закона, для которого задана политика отсечения)
+    pub fn validate(&self) -> Result<()> {
+        let valid = match self {
+            Distribution::Deterministic { value } => *value >= 0.,
+            Distribution::Uniform { low, .. } => *low >= 0. && self.law().validate().is_ok(),
+            Distribution::Empirical { values, .. } => {
+                !values.is_empty() && values.iter().all(|value| *value >= 0.)
+            }
+            _ => self.law().validate().is_ok(),
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Bad time distribution {:?}", self))
+        }
+    }
+
+    /// Соответствующий закон из [utils::distribution], для постоянного времени - вырожденный
+    /// эмпирический закон с одним значением
+    pub fn law(&self) -> Law {
+        match self {
+            Distribution::Deterministic { value } => Law::Empirical {
+                values: vec![*value],
+                weights: vec![1.],
+            },
+            Distribution::Uniform { low, high } => Law::Uniform {
+                low: *low,
+                high: *high,
+            },
+            Distribution::Exponential { rate } => Law::Exponential { rate: *rate },
+            Distribution::Normal {
+                mean, deviation, ..
+            } => Law::Normal {
+                mean: *mean,
+                deviation: *deviation,
+            },
+            Distribution::Erlang { shape, rate } => Law::Erlang {
+                shape: *shape,
+                rate: *rate,
+            },
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => Law::Hyperexponential {
+                probabilities: probabilities.clone(),
+                rates: rates.clone(),
+            },
+            Distribution::Empirical { values, .. } => Law::Empirical {
+                weights: vec![1.; values.len()],
+                values: values.clone(),
+            },
+        }
+    }
+
+    /// Теоретическое мат. ожидание (для нормального закона - без учета отсечения)
+    pub fn mean(&self) -> f64 {
+        self.law().mean()
+    }
+
+    /// Случайное неотрицательное время
+    pub fn sample(&self, rng: &mut dyn Generator) -> f64 {
+        match self {
+            Distribution::Deterministic { value } => *value,
+            Distribution::Normal {
+                mean,
+                deviation,
+                truncation,
+            } => truncation.apply(rng, |rng| rng::next(rng, *mean, *deviation)),
+            Distribution::Empirical { values, .. } => {
+                values[rng.next_in_range(0, values.len() as u32) as usize]
+            }
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => {
+                let rate = rates[distribution::choose(rng, probabilities.iter().copied())];
+                Law::Exponential { rate }.sample(rng)
+            }
+            // остальные законы не выделяют память при построении
+            _ => self.law().sample(rng),
+        }
+    }
+
+    /// Случайное время, округленное до целого числа раундов
+    pub fn next_time(&self, rng: &mut dyn Generator) -> usize {
+        self.sample(rng).round() as usize
+    }
+
+    /// Проверяет по критерию Колмогорова-Смирнова выборку размера `size` на соответствие закону.
+    /// Для постоянного времени и эмпирического закона критерий не применим, вернет None
+    pub fn check(&self, rng: &mut dyn Generator, size: usize) -> Option<KolmogorovSmirnov> {
+        match self {
+            Distribution::Normal {
+                mean, deviation, ..
+            } => Some(rng::check_normal(rng, *mean, *deviation, size)),
+            _ => rng::check_law(rng, &self.law(), size),
+        }
+    }
 }
 
 /// Заданные пользователем настройки системы
-#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Options {
     /// Распределение для интервала появления
     pub appearance_time: Distribution,
     /// Распределения для длительности задачи
     pub task_weight_time: Distribution,
-    /// Вероятность получении задачей низкого приоритета [0, 1]
-    pub low_priority_probability: f64,
-    /// Скольким миллисекундам равен один раунд
+    /// Классы приоритета, от высшего к низшему. По умолчанию один класс с дисциплиной FIFO
+    #[serde(default = "default_classes")]
+    pub classes: Vec<PriorityClass>,
+    /// Количество параллельно работающих приборов (каналов обслуживания)
+    #[serde(default = "default_servers")]
+    pub servers: usize,
+    /// Общая вместимость всех очередей: сколько задач может ждать обслуживания (задачи на приборах
+    /// не считаются, для системы M/M/c/K вместимость равна K - c). None - без ограничения
+    #[serde(default)]
+    pub capacity: Option<usize>,
+    /// Что делать с задачей, если для нее нет места в очереди
+    #[serde(default)]
+    pub overflow: Overflow,
+    /// Распределение допустимого времени от появления задачи до окончания ее обслуживания.
+    /// Если не задано, срок задачи равен моменту ее появления
+    #[serde(default)]
+    pub deadline_time: Option<Distribution>,
+    /// Скольким миллисекундам равен один раунд (не используется при пакетном запуске)
+    #[serde(default)]
     pub time_scale_millis: u64,
     /// Количество циклов эмуляции
     pub max_number_of_rounds: usize,
+    /// Генератор случайных чисел, используемый эмуляцией
+    #[serde(default)]
+    pub generator: GeneratorKind,
+    /// Начальное значение генератора, если не задано - берется из часов
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+/// Класс приоритета задач со своей очередью
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PriorityClass {
+    /// Вероятность, что новая задача попадет в этот класс (вероятности всех классов нормируются)
+    pub probability: f64,
+    /// Дисциплина обслуживания очереди класса
+    #[serde(default)]
+    pub discipline: Discipline,
+    /// Вместимость очереди класса, None - без ограничения
+    #[serde(default)]
+    pub capacity: Option<usize>,
+}
+
+/// Политика при переполнении очереди
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Default)]
+pub enum Overflow {
+    /// Новая задача отклоняется
+    #[default]
+    Reject,
+    /// Новая задача занимает место самой старой задачи своей очереди, старая теряется.
+    /// Если своя очередь пуста, а переполнена общая вместимость - место освобождается
+    /// в очереди самого низкого приоритета
+    DropOldest,
+    /// Задача вытесняет последнюю пришедшую задачу самого низкого приоритета ниже своего,
+    /// если переполнена только общая вместимость. Иначе новая задача отклоняется
+    PushOut,
+}
+
+/// По умолчанию все задачи одного приоритета
+fn default_classes() -> Vec<PriorityClass> {
+    vec![PriorityClass {
+        probability: 1.,
+        discipline: Discipline::Fifo,
+        capacity: None,
+    }]
+}
+
+/// По умолчанию система одноканальная
+fn default_servers() -> usize {
+    1
+}
+
+impl Options {
+    /// Читает эмпирические законы из файлов и проверяет параметры, см. [Options::validate]
+    pub fn load(mut self) -> Result<Self> {
+        self.appearance_time.load()?;
+        self.task_weight_time.load()?;
+        if let Some(deadline_time) = &mut self.deadline_time {
+            deadline_time.load()?;
+        }
+        self.validate()?;
+        Ok(self)
+    }
+
+    /// Проверяет параметры законов, классов приоритета и количество приборов.
+    /// Эмпирические законы из файлов должны быть уже прочитаны через [Options::load]
+    pub fn validate(&self) -> Result<()> {
+        if self.servers == 0 {
+            bail!("Number of servers should be positive");
+        }
+        if self.classes.is_empty()
+            || self.classes.iter().any(|class| class.probability < 0.)
+            || self.classes.iter().map(|class| class.probability).sum::<f64>() <= 0.
+        {
+            bail!("Priority class probabilities should be non-negative with positive sum");
+        }
+        self.appearance_time
+            .validate()
+            .context("Bad appearance_time")?;
+        self.task_weight_time
+            .validate()
+            .context("Bad task_weight_time")?;
+        if let Some(deadline_time) = &self.deadline_time {
+            deadline_time.validate().context("Bad deadline_time")?;
+        }
+        // иначе задачи появляются бесконечно в одном раунде
+        if self.appearance_time.mean() <= 0. {
+            bail!("Mean appearance time should be positive");
+        }
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -34,37 +296,119 @@ pub struct Task {
     pub incoming_time: usize,
     /// Требуемое время обслуживания
     pub require_time: usize,
-    /// Приоритет
-    pub low_priority: bool,
+    /// Срок, к которому задача должна быть обслужена
+    pub deadline: usize,
+    /// Класс приоритета, 0 - высший
+    pub priority: usize,
 }
 
 impl Task {
-    /// Вероятностное создание новой задачи
-    /// Генерация новой задача изходя из пройденного время, вероятности генерации и нормального закона распределения
-    pub fn try_new(now: usize, elapsed: usize, options: Options) -> Option<Self> {
-        // получаем случайно время появления новой задачи
-        let rand_appearance = next_int(
-            options.appearance_time.expectation_time,
-            options.appearance_time.dispersion_time,
-        );
-
-        // если это случайное время < прошедшего с последней генерации задачи - создаем новую, иначе - ничего
-        if rand_appearance <= elapsed {
-            Some(Task::new(now, options))
-        } else {
-            None
-        }
-    }
-
     /// Создание новой задачи
-    pub fn new(time: usize, options: Options) -> Self {
+    pub fn new(time: usize, options: &Options, rng: &mut dyn Generator) -> Self {
+        let require_time = options.task_weight_time.next_time(rng);
+        let priority = distribution::choose(rng, options.classes.iter().map(|c| c.probability));
+        let deadline = time
+            + options
+                .deadline_time
+                .as_ref()
+                .map_or(0, |deadline_time| deadline_time.next_time(rng));
         Task {
             incoming_time: time,
-            require_time: rng::next_int(
-                options.task_weight_time.expectation_time,
-                options.task_weight_time.dispersion_time,
-            ),
-            low_priority: rng::next_bool(options.low_priority_probability),
+            require_time,
+            deadline,
+            priority,
+        }
+    }
+}
+
+/// Прибор (канал обслуживания)
+#[derive(Clone, Debug, Default)]
+pub struct Server {
+    /// Обслуживаемая задача
+    pub task: Option<Task>,
+
+    /// Сколько времени осталось выполнять текущую задачу (если 0 - прибор свободен)
+    pub rest_time_working: usize,
+
+    /// Сколько раундов прибор был занят
+    pub busy_time: usize,
+
+    /// Сколько задач прибор обслужил до конца
+    pub task_served_total: usize,
+}
+
+impl Server {
+    /// Статистика прибора на момент `now`
+    fn get_stats(&self, now: usize) -> ServerStats {
+        ServerStats {
+            task: self.task.clone(),
+            task_time_spent: self
+                .task
+                .as_ref()
+                .map(|t| now + self.rest_time_working - t.incoming_time - t.require_time)
+                .unwrap_or(0),
+            rest_time_working: self.rest_time_working,
+            busy_time: self.busy_time,
+            task_served_total: self.task_served_total,
+            utilization: self.busy_time as f32 / now as f32,
+            throughput: self.task_served_total as f32 / now as f32,
+        }
+    }
+}
+
+/// Накопленные показатели класса приоритета
+#[derive(Clone, Debug, Default)]
+pub struct ClassState {
+    /// Всего задач поступило в систему
+    pub task_arrived_total: usize,
+
+    /// Всего задач выполнено (запущено на приборе)
+    pub task_done_total: usize,
+
+    /// Всего задач потеряно: отклонено или вытеснено из очереди
+    pub task_lost_total: usize,
+
+    /// Общее время ожидания запущенных задач
+    pub task_wait_time_total: usize,
+
+    /// Макс время ожидания в очереди запущенной задачи
+    pub task_max_wait_time: usize,
+}
+
+impl ClassState {
+    /// Добавляет показатели другого класса, чтобы получить показатели по всем классам
+    fn add(&mut self, other: &ClassState) {
+        self.task_arrived_total += other.task_arrived_total;
+        self.task_done_total += other.task_done_total;
+        self.task_lost_total += other.task_lost_total;
+        self.task_wait_time_total += other.task_wait_time_total;
+        self.task_max_wait_time = max(self.task_max_wait_time, other.task_max_wait_time);
+    }
+
+    /// Статистика на момент `now` с учетом задач, ожидающих в очередях `queues`
+    fn get_stats(&self, queues: &[&dyn Queue], now: usize) -> ClassStats {
+        // время ожидания задач, которые еще в очереди
+        let waiting = || {
+            queues
+                .iter()
+                .flat_map(|queue| queue.tasks())
+                .map(|t| now - t.incoming_time)
+        };
+        let task_in_q = queues.iter().map(|queue| queue.len()).sum::<usize>();
+
+        // количество задач запущенных и в очереди
+        let total_task = self.task_done_total + task_in_q;
+
+        ClassStats {
+            task_arrived_total: self.task_arrived_total,
+            task_done_total: self.task_done_total,
+            task_lost_total: self.task_lost_total,
+            task_in_q_total: task_in_q,
+            avg_task_wait_time: (self.task_wait_time_total + waiting().sum::<usize>()) as f32
+                / total_task as f32,
+            task_max_wait_time: max(self.task_max_wait_time, waiting().max().unwrap_or(0)),
+            avg_time_between_tasks: now as f32 / self.task_arrived_total as f32,
+            loss_rate: self.task_lost_total as f32 / self.task_arrived_total as f32,
         }
     }
 }
@@ -75,149 +419,127 @@ pub struct State {
     /// Если true эмуляция запущена
     pub started: bool,
 
+    /// Начальное значение генератора, с которым запущена эмуляция
+    pub seed: u64,
+
     /// Текущий момент времени (модельного времени)
     pub now: usize,
 
-    /// Сколько времени осталось выполнять текущую задачу (если 0 - нет запущенных задач)
-    pub rest_time_working: u32,
-
-    /// Очередь для задач с нормальным приоритетом (FIFO)
-    pub queue: VecDeque<Task>,
+    /// Момент появления следующей задачи. Интервалы между задачами не округляются,
+    /// задача попадает в очередь в первом раунде не раньше этого момента
+    pub next_arrival: f64,
 
-    /// Очередь задач с нижким приоритетом (LIFO)
-    pub low_prior_queue: Vec<Task>,
+    /// Очереди классов приоритета, от высшего к низшему
+    pub queues: Vec<Box<dyn Queue>>,
 
-    /// Запущенная задача
-    pub task: Option<Task>,
+    /// Приборы, обслуживающие задачи параллельно
+    pub servers: Vec<Server>,
 
     // Аккумуляторы
-    /// Всего задач выполнено
-    pub task_done_total: usize,
+    /// Показатели каждого класса приоритета
+    pub classes: Vec<ClassState>,
 
-    /// Всего низкоприоритетных задач выполнено
-    pub low_prior_task_done_total: usize,
+    /// Сколько поступивших задач застали очередь переполненной
+    pub task_blocked_total: usize,
 
-    /// Общее время ожидания завершенных задач
-    pub task_wait_time_total: usize,
-    /// Общее время ожидания завершенных низкоприоритетных задач
-    pub low_prior_task_wait_time_total: usize,
-
-    /// Макс время ожидания в обычной задачи очереди
-    pub normal_prior_task_max_wait_time_total: usize,
-    /// Макс время ожидания в очереди для низкориоритетных задач
-    pub low_prior_task_max_wait_time_total: usize,
+    /// Сколько запущенных задач будут обслужены позже своего срока
+    pub deadline_missed_total: usize,
 
     /// Сумма задач в очередях (для расчета среднего размера очереди)
     pub task_wait_in_q_total: usize,
 
+    /// Время ожидания каждой запущенной задачи (для расчета статистики)
+    pub wait_times: Vec<usize>,
+
     /// Минимальное время обслуживание задачи
     pub min_task_time_require: usize,
-    /// Нагрузка прибора в процентах
+    /// Сколько раундов были заняты приборы, в сумме по всем приборам
     pub load: usize,
 }
 
 impl State {
-    /// Новое пустоя состояние
-    pub fn new() -> Self {
+    /// Новое пустоя состояние системы с приборами и очередями согласно настройкам
+    pub fn new(seed: u64, options: &Options) -> Self {
         State {
             started: false,
+            seed,
             now: 0,
-            rest_time_working: 0,
-            queue: Default::default(),
-            low_prior_queue: Default::default(),
-            task_done_total: 0,
-            low_prior_task_done_total: 0,
-            task_wait_time_total: 0,
-            low_prior_task_wait_time_total: 0,
-            normal_prior_task_max_wait_time_total: 0,
-            task: None,
+            next_arrival: 0.,
+            queues: options
+                .classes
+                .iter()
+                .map(|class| class.discipline.queue())
+                .collect(),
+            servers: vec![Server::default(); options.servers],
+            classes: vec![ClassState::default(); options.classes.len()],
+            task_blocked_total: 0,
+            deadline_missed_total: 0,
             task_wait_in_q_total: 0,
-            low_prior_task_max_wait_time_total: 0,
-            min_task_time_require: usize::max_value(),
-            load: 0
+            wait_times: vec![],
+            min_task_time_require: usize::MAX,
+            load: 0,
         }
     }
 
+    /// Сколько задач во всех очередях
+    pub fn task_in_q(&self) -> usize {
+        self.queues.iter().map(|queue| queue.len()).sum()
+    }
+
     /// Считает статистику для текущего состояния системы, эта статистика отправляеться в полльзовательский интерфейс
     pub fn get_stats(&self) -> Stats {
-        let task_time_spent = self
-            .task
-            .as_ref()
-            .map(|t| self.now - t.incoming_time - t.require_time + self.rest_time_working as usize)
-            .unwrap_or(0);
-
-        // суммарное время ожидания всех нормальных задач в очереди
-        let total_wait_time_in_q: usize =
-            self.queue.iter().map(|t| self.now - t.incoming_time).sum();
-        // суммарное время ожидания всех низуоприоритетных задач в очереди
-        let total_wait_time_in_low_prior_q: usize = self
-            .low_prior_queue
+        let servers = self
+            .servers
             .iter()
-            .map(|t| self.now - t.incoming_time)
-            .sum();
+            .map(|server| server.get_stats(self.now))
+            .collect::<Vec<_>>();
 
-        // Сколько задач в очередях
-        let task_in_q = self.queue.len() + self.low_prior_queue.len();
-
-        // суммарное время ожидания всех задач завершенных и в очереди
-        let total_wait_time =
-            self.task_wait_time_total + total_wait_time_in_q + total_wait_time_in_low_prior_q;
-        // суммарное время ожидания всех низуоприоритетных задач завершенных и в очереди
-        let total_wait_time_low_prior =
-            self.low_prior_task_wait_time_total + total_wait_time_in_low_prior_q;
-
-        // количество всех задач законченый и в очередях
-        let total_task = self.task_done_total + task_in_q;
-        // количество всех низкоприоритетных задач законченных и в очередях
-        let total_low_task = self.low_prior_task_done_total + self.low_prior_queue.len();
-        // количество всех обычных задач законченных и в очередях
-        let total_norm_task = total_task - total_low_task;
-
-        // максимальное время ожидания всех нормальных задач в очереди
-        let max_wait_time_in_q: usize = self
-            .queue
+        let classes = self
+            .classes
             .iter()
-            .map(|t| self.now - t.incoming_time)
-            .max()
-            .unwrap_or(0);
-        // максимальное время ожидания всех низуоприоритетных задач в очереди
-        let max_wait_time_in_low_prior_q: usize = self
-            .low_prior_queue
+            .zip(&self.queues)
+            .map(|(class, queue)| class.get_stats(&[queue.as_ref()], self.now))
+            .collect::<Vec<_>>();
+
+        // показатели всех классов вместе
+        let mut total = ClassState::default();
+        for class in &self.classes {
+            total.add(class);
+        }
+        let queues = self
+            .queues
             .iter()
-            .map(|t| self.now - t.incoming_time)
-            .max()
-            .unwrap_or(0);
+            .map(|queue| queue.as_ref())
+            .collect::<Vec<_>>();
+        let total = total.get_stats(&queues, self.now);
 
         Stats {
+            seed: self.seed,
             now: self.now,
-            task: self.task.clone(),
-            task_time_spent,
-            rest_time_working: self.rest_time_working,
-            task_done_total: self.task_done_total,
-            low_prior_task_done_total: self.low_prior_task_done_total,
-            task_in_q_total: task_in_q,
-            low_prior_task_in_q_total: self.low_prior_queue.len(),
-
-            avg_task_in_q: self.task_wait_in_q_total as f32 / task_in_q as f32,
-            avg_task_wait_time: total_wait_time as f32 / total_task as f32,
-            low_prior_avg_task_wait_time: total_wait_time_low_prior as f32 / total_low_task as f32,
-            normal_prior_avg_task_wait_time: (total_wait_time - total_wait_time_low_prior) as f32
-                / total_norm_task as f32,
-
-            normal_prior_task_max_wait_time_total: max(
-                self.normal_prior_task_max_wait_time_total,
-                max_wait_time_in_q,
-            ),
-            low_prior_task_max_wait_time_total: max(
-                self.low_prior_task_max_wait_time_total,
-                max_wait_time_in_low_prior_q,
-            ),
-
-            avg_time_between_tasks: self.now as f32 / total_task as f32,
-            avg_time_between_low_prior_tasks: self.now as f32 / total_low_task as f32,
-            avg_time_between_normal_prior_tasks: self.now as f32 / total_norm_task as f32,
-            min_task_time_require: if self.min_task_time_require == usize::max_value() { -1 } else { self.min_task_time_require  as i32 },
-            load: self.load
+            utilization: self.load as f32 / (self.now * self.servers.len()) as f32,
+            throughput: servers.iter().map(|server| server.throughput).sum(),
+            servers,
+
+            task_arrived_total: total.task_arrived_total,
+            task_done_total: total.task_done_total,
+            task_lost_total: total.task_lost_total,
+            task_in_q_total: total.task_in_q_total,
+            avg_task_in_q: self.task_wait_in_q_total as f32 / total.task_in_q_total as f32,
+            avg_task_wait_time: total.avg_task_wait_time,
+            task_max_wait_time: total.task_max_wait_time,
+            avg_time_between_tasks: total.avg_time_between_tasks,
+            blocking_probability: self.task_blocked_total as f32
+                / total.task_arrived_total as f32,
+            loss_rate: total.loss_rate,
+            deadline_missed_total: self.deadline_missed_total,
+            classes,
+
+            min_task_time_require: if self.min_task_time_require == usize::MAX {
+                -1
+            } else {
+                self.min_task_time_require as i32
+            },
+            load: self.load,
         }
     }
 }
@@ -228,51 +550,87 @@ impl State {
 ///
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Stats {
+    /// Начальное значение генератора, позволяет воспроизвести эмуляцию
+    pub seed: u64,
+
     /// Текущий момент времени (модельного времени)
     now: usize,
 
-    /// Информация о текущей задаче
-    task: Option<Task>,
-
-    /// Сколько прождал в очереди
-    task_time_spent: usize,
-
-    /// Осталось обработывать текущую задачу
-    rest_time_working: u32,
+    /// Состояние и статистика каждого прибора
+    pub servers: Vec<ServerStats>,
+    /// Средняя загрузка приборов: доля времени, когда прибор занят
+    pub utilization: f32,
+    /// Пропускная способность системы: обслуженных задач за единицу времени
+    pub throughput: f32,
 
+    /// Всего задач поступило
+    pub task_arrived_total: usize,
     /// Всего задач выполнено
     pub task_done_total: usize,
-    /// Всего задач низкого приоритета выполнено
-    pub low_prior_task_done_total: usize,
-
+    /// Всего задач потеряно: отклонено или вытеснено из очереди
+    pub task_lost_total: usize,
     /// Всего задач в очереди
     pub task_in_q_total: usize,
-    /// Всего низкоприоритетных задач в очереди
-    pub low_prior_task_in_q_total: usize,
     /// Средняя длинна очереди
     pub avg_task_in_q: f32,
-
     /// Среднее время ожидания
     pub avg_task_wait_time: f32,
-    /// Среднее время ожидания низкоприоритетных задач
-    pub low_prior_avg_task_wait_time: f32,
-    /// Среднее время ожидания обычных задач
-    pub normal_prior_avg_task_wait_time: f32,
-
-    /// Макс время ожидания обычной задачи в очереди
-    pub normal_prior_task_max_wait_time_total: usize,
-    /// Макс время ожидания в очереди для низкориоритетных задач
-    pub low_prior_task_max_wait_time_total: usize,
-
-    /// Среднее время между появления всех задач
+    /// Макс время ожидания в очереди
+    pub task_max_wait_time: usize,
+    /// Среднее время между появления задач
     pub avg_time_between_tasks: f32,
-    /// Среднее время между появления низкоприоритетных задач
-    pub avg_time_between_low_prior_tasks: f32,
-    /// Среднее время между появления обычных задач
-    pub avg_time_between_normal_prior_tasks: f32,
+    /// Вероятность блокировки: доля поступивших задач, заставших очередь переполненной
+    pub blocking_probability: f32,
+    /// Доля потерянных задач
+    pub loss_rate: f32,
+    /// Сколько запущенных задач будут обслужены позже своего срока
+    pub deadline_missed_total: usize,
+
+    /// Статистика каждого класса приоритета, от высшего к низшему
+    pub classes: Vec<ClassStats>,
 
     /// Минимальное время обслуживание задачи
     pub min_task_time_require: i32,
-    /// Нагрузка прибора в процентах
+    /// Сколько раундов были заняты приборы, в сумме по всем приборам
     pub load: usize,
 }
+
+/// Статистика класса приоритета
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ClassStats {
+    /// Всего задач поступило
+    pub task_arrived_total: usize,
+    /// Всего задач выполнено
+    pub task_done_total: usize,
+    /// Всего задач потеряно
+    pub task_lost_total: usize,
+    /// Всего задач в очереди
+    pub task_in_q_total: usize,
+    /// Среднее время ожидания запущенных задач и задач в очереди
+    pub avg_task_wait_time: f32,
+    /// Макс время ожидания в очереди
+    pub task_max_wait_time: usize,
+    /// Среднее время между появлениями задач
+    pub avg_time_between_tasks: f32,
+    /// Доля потерянных задач
+    pub loss_rate: f32,
+}
+
+/// Состояние и статистика прибора
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ServerStats {
+    /// Информация о текущей задаче
+    pub task: Option<Task>,
+    /// Сколько текущая задача прождала в очереди
+    pub task_time_spent: usize,
+    /// Осталось обработывать текущую задачу
+    pub rest_time_working: usize,
+    /// Сколько раундов прибор был занят
+    pub busy_time: usize,
+    /// Сколько задач прибор обслужил до конца
+    pub task_served_total: usize,
+    /// Загрузка: доля времени, когда прибор занят
+    pub utilization: f32,
+    /// Пропускная способность: обслуженных задач за единицу времени
+    pub throughput: f32,
+}
@@ -0,0 +1,318 @@
+//! Очереди задач с разными дисциплинами обслуживания
+
+use crate::smo_engine::model::Task;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::Debug;
+use utils::generator::Generator;
+
+/// Очередь задач: дисциплина определяет, какая задача будет обслужена следующей
+pub trait Queue: Debug + Send {
+    /// Кладет задачу в очередь
+    fn push(&mut self, task: Task);
+
+    /// Достает следующую задачу для обслуживания
+    fn pop(&mut self, rng: &mut dyn Generator) -> Option<Task>;
+
+    /// Достает задачу, пришедшую раньше всех (для вытеснения при переполнении)
+    fn pop_oldest(&mut self) -> Option<Task>;
+
+    /// Достает задачу, пришедшую позже всех (для вытеснения при переполнении)
+    fn pop_newest(&mut self) -> Option<Task>;
+
+    /// Количество задач в очереди
+    fn len(&self) -> usize;
+
+    /// Вернет true, если очередь пуста
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Задачи в очереди в произвольном порядке
+    fn tasks(&self) -> Box<dyn Iterator<Item = &Task> + '_>;
+}
+
+/// Дисциплина обслуживания очереди
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Default)]
+pub enum Discipline {
+    /// Первым пришел - первым обслужен
+    #[default]
+    Fifo,
+    /// Последним пришел - первым обслужен
+    Lifo,
+    /// Задача выбирается случайно
+    Siro,
+    /// Первой обслуживается самая короткая задача
+    Sjf,
+    /// Первой обслуживается самая длинная задача
+    Ljf,
+    /// Первой обслуживается задача с самым ранним сроком завершения
+    Edf,
+}
+
+impl Discipline {
+    /// Пустая очередь с этой дисциплиной
+    pub fn queue(self) -> Box<dyn Queue> {
+        match self {
+            Discipline::Fifo => Box::new(Fifo::default()),
+            Discipline::Lifo => Box::new(Lifo::default()),
+            Discipline::Siro => Box::new(Siro::default()),
+            Discipline::Sjf => Box::new(Ordered::new(|task| task.require_time as i64)),
+            Discipline::Ljf => Box::new(Ordered::new(|task| -(task.require_time as i64))),
+            Discipline::Edf => Box::new(Ordered::new(|task| task.deadline as i64)),
+        }
+    }
+}
+
+/// Первым пришел - первым обслужен
+#[derive(Debug, Default)]
+pub struct Fifo {
+    tasks: VecDeque<Task>,
+}
+
+impl Queue for Fifo {
+    fn push(&mut self, task: Task) {
+        self.tasks.push_back(task)
+    }
+
+    fn pop(&mut self, _rng: &mut dyn Generator) -> Option<Task> {
+        self.tasks.pop_front()
+    }
+
+    fn pop_oldest(&mut self) -> Option<Task> {
+        self.tasks.pop_front()
+    }
+
+    fn pop_newest(&mut self) -> Option<Task> {
+        self.tasks.pop_back()
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn tasks(&self) -> Box<dyn Iterator<Item = &Task> + '_> {
+        Box::new(self.tasks.iter())
+    }
+}
+
+/// Последним пришел - первым обслужен
+#[derive(Debug, Default)]
+pub struct Lifo {
+    tasks: VecDeque<Task>,
+}
+
+impl Queue for Lifo {
+    fn push(&mut self, task: Task) {
+        self.tasks.push_back(task)
+    }
+
+    fn pop(&mut self, _rng: &mut dyn Generator) -> Option<Task> {
+        self.tasks.pop_back()
+    }
+
+    fn pop_oldest(&mut self) -> Option<Task> {
+        self.tasks.pop_front()
+    }
+
+    fn pop_newest(&mut self) -> Option<Task> {
+        self.tasks.pop_back()
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn tasks(&self) -> Box<dyn Iterator<Item = &Task> + '_> {
+        Box::new(self.tasks.iter())
+    }
+}
+
+/// Случайный выбор задачи. Задачи хранятся в порядке прихода, извлечение за O(n)
+#[derive(Debug, Default)]
+pub struct Siro {
+    tasks: VecDeque<Task>,
+}
+
+impl Queue for Siro {
+    fn push(&mut self, task: Task) {
+        self.tasks.push_back(task)
+    }
+
+    fn pop(&mut self, rng: &mut dyn Generator) -> Option<Task> {
+        if self.tasks.is_empty() {
+            return None;
+        }
+        let idx = rng.next_in_range(0, self.tasks.len() as u32) as usize;
+        self.tasks.remove(idx)
+    }
+
+    fn pop_oldest(&mut self) -> Option<Task> {
+        self.tasks.pop_front()
+    }
+
+    fn pop_newest(&mut self) -> Option<Task> {
+        self.tasks.pop_back()
+    }
+
+    fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    fn tasks(&self) -> Box<dyn Iterator<Item = &Task> + '_> {
+        Box::new(self.tasks.iter())
+    }
+}
+
+/// Задача в куче: ключ дисциплины и порядковый номер прихода
+type Keyed = Reverse<(i64, usize, TaskEntry)>;
+
+/// Обертка, чтобы задача не участвовала в сравнении элементов кучи
+#[derive(Debug)]
+struct TaskEntry(Task);
+
+impl PartialEq for TaskEntry {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl Eq for TaskEntry {}
+
+impl PartialOrd for TaskEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TaskEntry {
+    fn cmp(&self, _: &Self) -> std::cmp::Ordering {
+        std::cmp::Ordering::Equal
+    }
+}
+
+/// Первой обслуживается задача с наименьшим ключом, при равных ключах - пришедшая раньше.
+/// Извлечение следующей задачи за O(log n), вытеснение при переполнении за O(n)
+#[derive(Debug)]
+pub struct Ordered {
+    heap: BinaryHeap<Keyed>,
+    key: fn(&Task) -> i64,
+    /// Сколько задач было положено, порядковый номер следующей
+    pushed: usize,
+}
+
+impl Ordered {
+    /// Пустая очередь, упорядоченная по ключу `key`
+    pub fn new(key: fn(&Task) -> i64) -> Self {
+        Ordered {
+            heap: BinaryHeap::new(),
+            key,
+            pushed: 0,
+        }
+    }
+
+    /// Достает задачу с наименьшим (или наибольшим, если `newest`) порядковым номером прихода
+    fn pop_by_arrival(&mut self, newest: bool) -> Option<Task> {
+        let mut entries = std::mem::take(&mut self.heap).into_vec();
+        let by_seq = entries
+            .iter()
+            .enumerate()
+            .map(|(idx, Reverse((_, seq, _)))| (*seq, idx));
+        let idx = if newest {
+            by_seq.max().map(|(_, idx)| idx)
+        } else {
+            by_seq.min().map(|(_, idx)| idx)
+        };
+        let task = idx.map(|idx| entries.swap_remove(idx).0 .2 .0);
+        self.heap = entries.into();
+        task
+    }
+}
+
+impl Queue for Ordered {
+    fn push(&mut self, task: Task) {
+        let key = (self.key)(&task);
+        self.heap.push(Reverse((key, self.pushed, TaskEntry(task))));
+        self.pushed += 1;
+    }
+
+    fn pop(&mut self, _rng: &mut dyn Generator) -> Option<Task> {
+        self.heap.pop().map(|Reverse((_, _, TaskEntry(task)))| task)
+    }
+
+    fn pop_oldest(&mut self) -> Option<Task> {
+        self.pop_by_arrival(false)
+    }
+
+    fn pop_newest(&mut self) -> Option<Task> {
+        self.pop_by_arrival(true)
+    }
+
+    fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    fn tasks(&self) -> Box<dyn Iterator<Item = &Task> + '_> {
+        Box::new(
+            self.heap
+                .iter()
+                .map(|Reverse((_, _, TaskEntry(task)))| task),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::generator::RustRng;
+
+    fn task(incoming_time: usize, require_time: usize, deadline: usize) -> Task {
+        Task {
+            incoming_time,
+            require_time,
+            deadline,
+            priority: 0,
+        }
+    }
+
+    /// Порядок обслуживания задач, пришедших в моменты 0, 1, 2, 3
+    fn order(discipline: Discipline) -> Vec<usize> {
+        let mut queue = discipline.queue();
+        for t in [task(0, 5, 9), task(1, 2, 4), task(2, 8, 3), task(3, 2, 7)].iter() {
+            queue.push(t.clone());
+        }
+        assert_eq!(queue.len(), 4);
+
+        let mut rng = RustRng::from_seed(1);
+        std::iter::from_fn(|| queue.pop(&mut rng))
+            .map(|t| t.incoming_time)
+            .collect()
+    }
+
+    #[test]
+    fn disciplines_order() {
+        assert_eq!(order(Discipline::Fifo), vec![0, 1, 2, 3]);
+        assert_eq!(order(Discipline::Lifo), vec![3, 2, 1, 0]);
+        // при равной длительности первой обслуживается пришедшая раньше
+        assert_eq!(order(Discipline::Sjf), vec![1, 3, 0, 2]);
+        assert_eq!(order(Discipline::Ljf), vec![2, 0, 1, 3]);
+        assert_eq!(order(Discipline::Edf), vec![2, 1, 3, 0]);
+
+        let mut siro = order(Discipline::Siro);
+        siro.sort_unstable();
+        assert_eq!(siro, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn pop_by_arrival() {
+        let mut queue = Discipline::Sjf.queue();
+        for t in [task(0, 5, 0), task(1, 2, 0), task(2, 8, 0)].iter() {
+            queue.push(t.clone());
+        }
+
+        assert_eq!(queue.pop_oldest().map(|t| t.incoming_time), Some(0));
+        assert_eq!(queue.pop_newest().map(|t| t.incoming_time), Some(2));
+        assert_eq!(queue.tasks().count(), 1);
+    }
+}
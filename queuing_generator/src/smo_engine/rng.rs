@@ -1,26 +1,157 @@
 //! Модуль с функциями генерации случайных чисел
 
-use rand::Rng;
+use serde::{Deserialize, Serialize};
+use utils::criteria::{self, Cdf, KolmogorovSmirnov};
+use utils::distribution::{self, Distribution};
+use utils::generator::Generator;
 
-/// Генерирует случайно число с нормальным распределением
-pub fn next(expectation: usize, dispersion: usize) -> f32 {
-    let mut rng = rand::thread_rng();
-    let mut sum = 0.;
+/// Сколько раз [Truncation::Resample] повторяет генерацию, прежде чем заменить значение нулем
+const MAX_RESAMPLES: usize = 1000;
 
-    for _ in 0..6 {
-        sum += rng.gen::<f32>();
+/// Что делать с отрицательными значениями времени
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Default)]
+pub enum Truncation {
+    /// Заменять нулем, вероятность отрицательных значений скапливается в нуле
+    Clamp,
+    /// Генерировать заново, получается закон, усеченный слева нулем
+    #[default]
+    Resample,
+    /// Брать модуль значения
+    Reflect,
+}
+
+impl Truncation {
+    /// Неотрицательное значение из `sample` согласно политике.
+    /// Если при повторной генерации за [MAX_RESAMPLES] попыток не получено неотрицательное значение
+    /// (мат. ожидание сильно меньше нуля), возвращается 0
+    pub fn apply<F>(self, rng: &mut dyn Generator, mut sample: F) -> f64
+    where
+        F: FnMut(&mut dyn Generator) -> f64,
+    {
+        let x = sample(rng);
+        match self {
+            Truncation::Clamp => x.max(0.),
+            Truncation::Reflect => x.abs(),
+            Truncation::Resample if x >= 0. => x,
+            Truncation::Resample => (0..MAX_RESAMPLES)
+                .map(|_| sample(rng))
+                .find(|&x| x >= 0.)
+                .unwrap_or(0.),
+        }
     }
+}
+
+/// Генерирует случайное число с нормальным распределением
+/// с мат. ожиданием `mean` и среднеквадратичным отклонением `deviation` (дисперсия равна `deviation^2`)
+pub fn next(rng: &mut dyn Generator, mean: f64, deviation: f64) -> f64 {
+    mean + deviation * distribution::standard_normal(rng)
+}
 
-    dispersion as f32 * (sum - 3 as f32) + expectation as f32
+/// Проверяет по критерию Колмогорова-Смирнова, что [next] действительно генерирует
+/// нормальное распределение с мат. ожиданием `mean` и среднеквадратичным отклонением `deviation`
+pub fn check_normal(
+    rng: &mut dyn Generator,
+    mean: f64,
+    deviation: f64,
+    size: usize,
+) -> KolmogorovSmirnov {
+    let sample = (0..size)
+        .map(|_| next(rng, mean, deviation))
+        .collect::<Vec<_>>();
+
+    let law = Cdf::Normal { mean, deviation };
+    criteria::kolmogorov_smirnov(&sample, |x| law.value(x))
 }
 
-/// Генерирует целое случайно число с нормальным распределением
-pub fn next_int(expectation: usize, dispersion: usize) -> usize {
-    let res = next(expectation, dispersion) as usize;
-    res
+/// Проверяет по критерию Колмогорова-Смирнова, что выборка закона `law` ему соответствует.
+/// Для дискретных законов критерий не применим, вернет None
+pub fn check_law(
+    rng: &mut dyn Generator,
+    law: &Distribution,
+    size: usize,
+) -> Option<KolmogorovSmirnov> {
+    if !law.continuous() {
+        return None;
+    }
+
+    let sample = law.sample_n(rng, size);
+    Some(criteria::kolmogorov_smirnov(&sample, |x| law.cdf(x)))
 }
 
-/// Генерирует булевое значение с нормальный распределением
-pub fn next_bool(probability: f64) -> bool {
-    rand::thread_rng().gen_bool(probability)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::generator::RustRng;
+
+    const SIZE: usize = 200_000;
+
+    /// Выборочные мат. ожидание, дисперсия, асимметрия и эксцесс
+    fn moments(sample: &[f64]) -> (f64, f64, f64, f64) {
+        let n = sample.len() as f64;
+        let mean = sample.iter().sum::<f64>() / n;
+        let central = |k: i32| sample.iter().map(|x| (x - mean).powi(k)).sum::<f64>() / n;
+        let dispersion = central(2);
+        (
+            mean,
+            dispersion,
+            central(3) / dispersion.powf(1.5),
+            central(4) / (dispersion * dispersion) - 3.,
+        )
+    }
+
+    #[test]
+    fn normal_moments() {
+        let (mean, deviation) = (5., 2.);
+        let mut rng = RustRng::from_seed(20);
+        let sample = (0..SIZE)
+            .map(|_| next(&mut rng, mean, deviation))
+            .collect::<Vec<_>>();
+
+        let (m, d, skewness, kurtosis) = moments(&sample);
+        let n = SIZE as f64;
+        // допуск - 4 стандартные ошибки соответствующей оценки
+        assert!((m - mean).abs() < 4. * deviation / n.sqrt(), "mean {}", m);
+        assert!(
+            (d - deviation * deviation).abs() < 4. * deviation * deviation * (2. / n).sqrt(),
+            "dispersion {}",
+            d
+        );
+        assert!(
+            skewness.abs() < 4. * (6. / n).sqrt(),
+            "skewness {}",
+            skewness
+        );
+        assert!(
+            kurtosis.abs() < 4. * (24. / n).sqrt(),
+            "kurtosis {}",
+            kurtosis
+        );
+
+        let ks = check_normal(&mut RustRng::from_seed(20), mean, deviation, 10_000);
+        assert!(ks.p_value > 0.001, "{:?}", ks);
+    }
+
+    /// При m = 0 половина значений отрицательна: Clamp дает 0 с вероятностью 1/2,
+    /// Resample и Reflect - полунормальный закон со средним σ √(2 / π)
+    #[test]
+    fn truncation() {
+        let deviation = 3.;
+        let half_normal = deviation * (2. / std::f64::consts::PI).sqrt();
+        let tolerance = 4. * deviation / (SIZE as f64).sqrt();
+
+        for &truncation in [Truncation::Clamp, Truncation::Resample, Truncation::Reflect].iter() {
+            let mut rng = RustRng::from_seed(21);
+            let sample = (0..SIZE)
+                .map(|_| truncation.apply(&mut rng, |rng| next(rng, 0., deviation)))
+                .collect::<Vec<_>>();
+            assert!(sample.iter().all(|&x| x >= 0.), "{:?}", truncation);
+
+            let (m, ..) = moments(&sample);
+            let expected = match truncation {
+                Truncation::Clamp => half_normal / 2.,
+                Truncation::Resample | Truncation::Reflect => half_normal,
+            };
+            assert!((m - expected).abs() < tolerance, "{:?}: {}", truncation, m);
+        }
+    }
 }
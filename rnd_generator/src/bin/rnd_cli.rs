@@ -0,0 +1,364 @@
+//! Запуск генераторов без графического интерфейса
+//!
+//! ```text
+//! rnd_cli gen   <kind> <size> [--seed <seed>] [--float]
+//! rnd_cli stats <kind> <size> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+//!                             [--max-lag <lag>] [--hist-bins <bins|sturges|fd>] [--float]
+//!                             [--level <confidence level>] [--quantiles <q1,q2,...>]
+//! rnd_cli stream <kind> <size> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+//!                              [--float] [--level <confidence level>]
+//!                              [--chunk <size>] [--threads <threads>]
+//! rnd_cli pi    <kind> <size> [--seed <seed>] [--format json|csv|table] [--y-kind <kind>]
+//!                             [--substreams] [--checkpoints <count>] [--level <confidence level>]
+//! rnd_cli sample <kind> <size> <distribution> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+//!                                            [--max-lag <lag>] [--hist-bins <bins|sturges|fd>]
+//!                                            [--level <confidence level>] [--quantiles <q1,q2,...>]
+//! rnd_cli cycle <kind> <max_iterations> [--seed <seed>] [--format json|csv|table]
+//! rnd_cli integrate <kind> <size> [--seed <seed>] [--format json|csv|table]
+//!                                 [--integrand sin|gauss|ball|squares] [--method hitOrMiss|sampleMean]
+//!                                 [--dimensions <n>] [--level <confidence level>]
+//! ```
+//!
+//! `kind` и `size` такие же, как в строке `gen|kind|size` графического интерфейса.
+//! С `--float` генерируются непрерывные величины в [0, 1) вместо целых чисел в [0, 100).
+//! Если `--seed` не задан, `gen` печатает начальное значение из часов в stderr.
+//! `stream` считает статистику порциями по `--chunk` значений, не храня выборку в памяти,
+//! поэтому подходит для очень больших выборок, но не считает квантили и автокорреляцию.
+//! Каждая порция берется из своего подпотока генератора, поэтому выборка отличается от `stats` с тем же `seed`.
+//! `sample` генерирует выборку закона распределения `distribution` в формате `имя:параметр:...`,
+//! например `exponential:0.5`, `normal:10:2`, `erlang:3:1` или `empirical:1,2,2,5`.
+//! `pi` по умолчанию берет координаты парами подряд из одного потока генератора `kind`.
+//! С `--y-kind` или `--substreams` координата y берется из независимого подпотока
+//! генератора `--y-kind` (по умолчанию того же типа).
+
+use anyhow::{anyhow, bail, Result};
+use rnd_generator::integrand::{self, Integrand};
+use serde::Serialize;
+use std::env;
+use std::str::FromStr;
+use utils::distribution::Distribution;
+use utils::generator;
+use utils::generator::{Cycle, GeneratorKind};
+use utils::histogram::Binning;
+use utils::monte_carlo::{IntegrationMethod, PiOptions};
+use utils::report::{self, Format};
+use utils::StatsOptions;
+
+const USAGE: &str = "Usage:
+    rnd_cli gen   <kind> <size> [--seed <seed>] [--float]
+    rnd_cli stats <kind> <size> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+                                [--max-lag <lag>] [--hist-bins <bins|sturges|fd>] [--float]
+                                [--level <confidence level>] [--quantiles <q1,q2,...>]
+    rnd_cli stream <kind> <size> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+                                 [--float] [--level <confidence level>]
+                                 [--chunk <size>] [--threads <threads>]
+    rnd_cli pi    <kind> <size> [--seed <seed>] [--format json|csv|table] [--y-kind <kind>]
+                                [--substreams] [--checkpoints <count>] [--level <confidence level>]
+    rnd_cli sample <kind> <size> <distribution> [--seed <seed>] [--format json|csv|table] [--bins <bins>]
+                                               [--max-lag <lag>] [--hist-bins <bins|sturges|fd>]
+                                               [--level <confidence level>] [--quantiles <q1,q2,...>]
+    rnd_cli cycle <kind> <max_iterations> [--seed <seed>] [--format json|csv|table]
+    rnd_cli integrate <kind> <size> [--seed <seed>] [--format json|csv|table]
+                                    [--integrand sin|gauss|ball|squares] [--method hitOrMiss|sampleMean]
+                                    [--dimensions <n>] [--level <confidence level>]
+
+kind: genRust, genLemer, genMinstd, genNumRecipes, genGlibc or genLcg:a:c:m
+distribution: uniform:low:high, exponential:rate, normal:mean:deviation, poisson:mean,
+              erlang:shape:rate, gamma:shape:scale, hyperexponential:p1,p2,...:rate1,rate2,...,
+              bernoulli:p, geometric:p or empirical:v1,v2,...";
+
+/// Размер порции по умолчанию для `stream`
+const DEFAULT_CHUNK: usize = 1 << 16;
+
+/// Разобранные аргументы командной строки
+struct CliCmd {
+    cmd: String,
+    kind: GeneratorKind,
+    size: usize,
+    seed: Option<u64>,
+    format: Format,
+    options: StatsOptions,
+    /// Генерировать непрерывные величины в [0, 1)
+    float: bool,
+    /// Размер порции для потокового расчета статистики
+    chunk: usize,
+    /// Количество потоков для потокового расчета статистики
+    threads: usize,
+    /// Генератор координаты y для расчета π в независимом подпотоке,
+    /// если не задан - координаты берутся парами из одного потока
+    y_kind: Option<GeneratorKind>,
+    /// Количество контрольных точек на кривой сходимости π
+    checkpoints: usize,
+    /// Функция для интегрирования
+    integrand: Integrand,
+    /// Метод интегрирования
+    method: IntegrationMethod,
+    /// Размерность области интегрирования для многомерных функций
+    dimensions: usize,
+    /// Закон распределения для `sample`
+    distribution: Option<Distribution>,
+}
+
+/// Результат поиска периода генератора
+#[derive(Serialize)]
+struct CycleResult {
+    seed: u64,
+    max_iterations: usize,
+    /// None, если период не найден за max_iterations шагов
+    cycle: Option<Cycle>,
+}
+
+fn main() -> Result<()> {
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let CliCmd {
+        cmd,
+        kind,
+        size,
+        seed,
+        format,
+        options,
+        float,
+        chunk,
+        threads,
+        y_kind,
+        checkpoints,
+        integrand,
+        method,
+        dimensions,
+        distribution,
+    } = parse_args(&args).map_err(|err| anyhow!("{}\n\n{}", err, USAGE))?;
+
+    // gen печатает только числа, поэтому начальное значение из часов выводим в stderr,
+    // чтобы запуск можно было повторить с --seed
+    let clock_seed = seed.is_none();
+    let seed = generator::resolve_seed(seed);
+    if cmd == "gen" && clock_seed {
+        eprintln!("seed: {}", seed);
+    }
+
+    match cmd.as_str() {
+        "gen" if float => {
+            for num in rnd_generator::generate_float(kind, size, seed)? {
+                println!("{}", num);
+            }
+        }
+        "gen" => {
+            for num in rnd_generator::generate_int(kind, size, seed)? {
+                println!("{}", num);
+            }
+        }
+        "stats" => {
+            let stats = if float {
+                let data = rnd_generator::generate_float(kind, size, seed)?;
+                let options = StatsOptions {
+                    range: Some(rnd_generator::FLOAT_RANGE),
+                    ..options
+                };
+                utils::stats(&data, &options)?
+            } else {
+                let data = rnd_generator::generate_int(kind, size, seed)?;
+                utils::stats(&data, &options)?
+            }
+            .with_seed(seed);
+            print!("{}", report::render(&stats, format)?);
+        }
+        "stream" => {
+            let stats = rnd_generator::stream_stats(
+                kind,
+                size,
+                seed,
+                float,
+                options.chi_squared_bins,
+                chunk,
+                threads,
+            )?
+            .summary(options.confidence_level)
+            .with_seed(seed);
+            print!("{}", report::render(&stats, format)?);
+        }
+        "pi" => {
+            let pi_options = PiOptions {
+                checkpoints,
+                confidence_level: options.confidence_level,
+            };
+            let estimate = rnd_generator::estimate_pi(kind, y_kind, size, seed, &pi_options)?;
+            print!("{}", report::render(&estimate, format)?);
+        }
+        "cycle" => {
+            let result = CycleResult {
+                seed,
+                max_iterations: size,
+                cycle: kind.find_cycle(seed, size)?,
+            };
+            print!("{}", report::render(&result, format)?);
+        }
+        "sample" => {
+            let distribution = distribution.expect("distribution is checked while parsing");
+            let sample =
+                rnd_generator::distribution_stats(kind, &distribution, size, seed, &options)?;
+            print!("{}", report::render(&sample, format)?);
+        }
+        "integrate" => {
+            let result = integrand::integrate(
+                kind,
+                integrand,
+                method,
+                dimensions,
+                size,
+                seed,
+                options.confidence_level,
+            )?;
+            print!("{}", report::render(&result, format)?);
+        }
+        _ => unreachable!("command is checked while parsing"),
+    }
+
+    Ok(())
+}
+
+/// Разбирает аргументы командной строки
+fn parse_args(args: &[String]) -> Result<CliCmd> {
+    if args.len() < 3 {
+        bail!("Expected command, generator kind and sample size");
+    }
+
+    let cmd = args[0].clone();
+    if ![
+        "gen",
+        "stats",
+        "stream",
+        "sample",
+        "pi",
+        "cycle",
+        "integrate",
+    ]
+    .contains(&cmd.as_str())
+    {
+        bail!("Unknown command {:?}", cmd);
+    }
+
+    let kind = GeneratorKind::from_str(&args[1])?;
+    let size =
+        usize::from_str(&args[2]).map_err(|err| anyhow!("Bad size {:?}: {}", args[2], err))?;
+    if size == 0 {
+        bail!("Size should be positive");
+    }
+
+    // у `sample` закон распределения идет сразу после размера выборки
+    let (distribution, rest) = if cmd == "sample" {
+        let spec = args
+            .get(3)
+            .ok_or_else(|| anyhow!("Expected distribution after sample size"))?;
+        (Some(Distribution::from_str(spec)?), &args[4..])
+    } else {
+        (None, &args[3..])
+    };
+
+    let mut seed = None;
+    let mut format = None;
+    let mut options = StatsOptions {
+        range: Some(rnd_generator::RANGE),
+        ..StatsOptions::default()
+    };
+
+    let mut float = false;
+    let mut chunk = DEFAULT_CHUNK;
+    let mut threads = 1;
+    let mut y_kind = None;
+    let mut substreams = false;
+    let mut integrand = Integrand::Sin;
+    let mut method = IntegrationMethod::SampleMean;
+    let mut dimensions = 2;
+    let mut checkpoints = PiOptions::default().checkpoints;
+
+    let mut rest = rest.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--float" {
+            float = true;
+            continue;
+        }
+        if arg == "--substreams" {
+            substreams = true;
+            continue;
+        }
+
+        let value = rest
+            .next()
+            .ok_or_else(|| anyhow!("Option {} requires a value", arg))?;
+        match arg.as_str() {
+            "--seed" => {
+                seed = Some(
+                    u64::from_str(value).map_err(|err| anyhow!("Bad seed {:?}: {}", value, err))?,
+                )
+            }
+            "--format" => format = Some(Format::from_str(value)?),
+            "--bins" => {
+                options.chi_squared_bins = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad number of bins {:?}: {}", value, err))?
+            }
+            "--max-lag" => {
+                options.max_lag = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad max lag {:?}: {}", value, err))?
+            }
+            "--hist-bins" => options.binning = Binning::from_str(value)?,
+            "--level" => options.confidence_level = StatsOptions::parse_confidence_level(value)?,
+            "--quantiles" => options.quantiles = StatsOptions::parse_quantiles(value)?,
+            "--chunk" => {
+                chunk = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad chunk size {:?}: {}", value, err))?
+            }
+            "--y-kind" => y_kind = Some(GeneratorKind::from_str(value)?),
+            "--checkpoints" => {
+                checkpoints = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad number of checkpoints {:?}: {}", value, err))?
+            }
+            "--integrand" => integrand = Integrand::from_str(value)?,
+            "--method" => method = IntegrationMethod::from_str(value)?,
+            "--dimensions" => {
+                dimensions = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad number of dimensions {:?}: {}", value, err))?
+            }
+            "--threads" => {
+                threads = usize::from_str(value)
+                    .map_err(|err| anyhow!("Bad number of threads {:?}: {}", value, err))?
+            }
+            _ => bail!("Unknown option {:?}", arg),
+        }
+    }
+
+    if substreams && y_kind.is_none() {
+        y_kind = Some(kind);
+    }
+
+    // gen печатает по одному числу в строке, формат к нему не применим
+    if cmd == "gen" && format.is_some() {
+        bail!("Option --format is not supported by gen, it prints one number per line");
+    }
+    if chunk == 0 || threads == 0 {
+        bail!("Chunk size and number of threads should be positive");
+    }
+    if dimensions == 0 || dimensions > integrand::MAX_DIMENSIONS {
+        bail!(
+            "Number of dimensions should be in [1, {}]",
+            integrand::MAX_DIMENSIONS
+        );
+    }
+
+    Ok(CliCmd {
+        cmd,
+        kind,
+        size,
+        seed,
+        format: format.unwrap_or(Format::Table),
+        options,
+        float,
+        chunk,
+        threads,
+        y_kind,
+        checkpoints,
+        integrand,
+        method,
+        dimensions,
+        distribution,
+    })
+}
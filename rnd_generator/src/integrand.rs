@@ -0,0 +1,190 @@
+//! Встроенные функции для проверки генераторов на задаче интегрирования методом Монте-Карло.
+//! Для каждой известно точное значение интеграла, поэтому можно сравнить с ним оценку
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::str::FromStr;
+use utils::generator::GeneratorKind;
+use utils::monte_carlo::{self, Integral, IntegrationMethod};
+use utils::special;
+
+/// Наибольшая размерность области интегрирования для ball и squares, как в графическом интерфейсе.
+/// Каждая точка хранится целиком, а объем шара в больших размерностях все равно почти нулевой
+pub const MAX_DIMENSIONS: usize = 20;
+
+/// Встроенная подынтегральная функция
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Integrand {
+    /// sin(x) на [0, π], интеграл равен 2
+    #[serde(rename = "sin")]
+    Sin,
+    /// exp(-x^2) на [0, 2], интеграл равен √π / 2 * erf(2)
+    #[serde(rename = "gauss")]
+    Gauss,
+    /// Индикатор единичного шара в кубе [-1, 1]^N, интеграл равен объему N-мерного шара
+    #[serde(rename = "ball")]
+    Ball,
+    /// x1^2 + ... + xN^2 на [0, 1]^N, интеграл равен N / 3
+    #[serde(rename = "squares")]
+    Squares,
+}
+
+impl FromStr for Integrand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sin" => Ok(Integrand::Sin),
+            "gauss" => Ok(Integrand::Gauss),
+            "ball" => Ok(Integrand::Ball),
+            "squares" => Ok(Integrand::Squares),
+            _ => Err(anyhow!(
+                "Unknown integrand {:?}, expected sin, gauss, ball or squares",
+                s
+            )),
+        }
+    }
+}
+
+impl Integrand {
+    /// Размерность области: sin и gauss одномерные, для остальных берется `dimensions`
+    pub fn dimensions(self, dimensions: usize) -> usize {
+        match self {
+            Integrand::Sin | Integrand::Gauss => 1,
+            Integrand::Ball | Integrand::Squares => dimensions.max(1),
+        }
+    }
+
+    /// Значение функции в точке
+    pub fn value(self, point: &[f64]) -> f64 {
+        match self {
+            Integrand::Sin => point[0].sin(),
+            Integrand::Gauss => (-point[0] * point[0]).exp(),
+            Integrand::Ball => {
+                if point.iter().map(|x| x * x).sum::<f64>() <= 1. {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Integrand::Squares => point.iter().map(|x| x * x).sum(),
+        }
+    }
+
+    /// Область интегрирования размерности `dimensions`
+    pub fn domain(self, dimensions: usize) -> Vec<(f64, f64)> {
+        let range = match self {
+            Integrand::Sin => (0., PI),
+            Integrand::Gauss => (0., 2.),
+            Integrand::Ball => (-1., 1.),
+            Integrand::Squares => (0., 1.),
+        };
+        vec![range; self.dimensions(dimensions)]
+    }
+
+    /// Диапазон значений функции на области, нужен для метода "попал - не попал"
+    pub fn bounds(self, dimensions: usize) -> (f64, f64) {
+        match self {
+            Integrand::Sin | Integrand::Gauss | Integrand::Ball => (0., 1.),
+            Integrand::Squares => (0., self.dimensions(dimensions) as f64),
+        }
+    }
+
+    /// Точное значение интеграла
+    pub fn exact(self, dimensions: usize) -> f64 {
+        let n = self.dimensions(dimensions) as f64;
+        match self {
+            Integrand::Sin => 2.,
+            Integrand::Gauss => PI.sqrt() / 2. * special::erf(2.),
+            Integrand::Ball => (n / 2. * PI.ln() - special::ln_gamma(n / 2. + 1.)).exp(),
+            Integrand::Squares => n / 3.,
+        }
+    }
+}
+
+/// Результат интегрирования встроенной функции вместе с точным значением
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IntegrandResult {
+    /// Функция
+    pub integrand: Integrand,
+    /// Точное значение интеграла
+    pub exact: f64,
+    /// Абсолютная ошибка |оценка - точное значение|
+    pub error: f64,
+    /// Оценка интеграла методом Монте-Карло
+    pub integral: Integral,
+}
+
+/// Интегрирует встроенную функцию по `size` случайным точкам генератора `kind`.
+/// Вернет ошибку, если `size` равен нулю или `dimensions` больше [MAX_DIMENSIONS]
+pub fn integrate(
+    kind: GeneratorKind,
+    integrand: Integrand,
+    method: IntegrationMethod,
+    dimensions: usize,
+    size: usize,
+    seed: u64,
+    confidence_level: f64,
+) -> Result<IntegrandResult> {
+    if size == 0 {
+        bail!("Size should be positive");
+    }
+    if dimensions > MAX_DIMENSIONS {
+        bail!("Number of dimensions should not exceed {}", MAX_DIMENSIONS);
+    }
+    let mut rng = kind.create(seed)?;
+    let domain = integrand.domain(dimensions);
+    let f = |point: &[f64]| integrand.value(point);
+
+    let integral = match method {
+        IntegrationMethod::SampleMean => {
+            monte_carlo::sample_mean(rng.as_mut(), f, &domain, size, confidence_level)
+        }
+        IntegrationMethod::HitOrMiss => monte_carlo::hit_or_miss(
+            rng.as_mut(),
+            f,
+            &domain,
+            integrand.bounds(dimensions),
+            size,
+            confidence_level,
+        ),
+    }
+    .with_seed(seed);
+
+    let exact = integrand.exact(dimensions);
+    Ok(IntegrandResult {
+        integrand,
+        exact,
+        error: (integral.value - exact).abs(),
+        integral,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        let integrate = |size, dimensions| {
+            integrate(
+                GeneratorKind::Rust,
+                Integrand::Ball,
+                IntegrationMethod::SampleMean,
+                dimensions,
+                size,
+                3,
+                0.95,
+            )
+        };
+
+        assert!(integrate(0, 2).is_err());
+        assert!(integrate(10, MAX_DIMENSIONS + 1).is_err());
+        assert!(integrate(10, 100_000_000_000).is_err());
+        assert_eq!(
+            integrate(10, MAX_DIMENSIONS).unwrap().integral.dimensions,
+            MAX_DIMENSIONS
+        );
+    }
+}
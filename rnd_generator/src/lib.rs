@@ -0,0 +1,268 @@
+//! Генераторы псевдослучайных числовых последовательностей.
+//!
+//! Библиотека не зависит от web-view, графический интерфейс собирается с фичей `gui`.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::thread;
+use utils::criteria::{self, KolmogorovSmirnov};
+use utils::distribution::Distribution;
+use utils::generator::{self, Generator, GeneratorKind};
+use utils::monte_carlo::{self, PiEstimate, PiOptions};
+use utils::online::OnlineStats;
+use utils::{Stats, StatsOptions};
+
+/// Встроенные функции для интегрирования методом Монте-Карло
+pub mod integrand;
+/// Генерация псевдослучайной последовательности методом Лемера
+pub mod lemer_gen;
+/// Встроенная в Rust генерация случайных чисел
+pub mod rust_gen;
+
+pub use utils;
+
+/// Диапазон [0, utils::MAX) целых чисел, которые выдает [generate_int]
+pub const RANGE: (f64, f64) = (0., utils::MAX as f64);
+/// Диапазон [0, 1) непрерывных величин, которые выдает [generate_float]
+pub const FLOAT_RANGE: (f64, f64) = (0., 1.);
+
+/// Генерирует массив случайных целых чисел в диапазоне [0, utils::MAX) выбранным генератором
+pub fn generate_int(kind: GeneratorKind, size: usize, seed: u64) -> Result<Vec<u32>> {
+    match kind.lemer_params() {
+        Some(params) => lemer_gen::generate_arr_int(params, size, utils::MAX as usize, seed),
+        None => Ok(rust_gen::generate(size, utils::MAX as usize, seed)),
+    }
+}
+
+/// Генерирует массив случайных чисел в диапазоне [0, 1) выбранным генератором
+pub fn generate_float(kind: GeneratorKind, size: usize, seed: u64) -> Result<Vec<f32>> {
+    match kind.lemer_params() {
+        Some(params) => lemer_gen::generate_arr(params, size, seed),
+        None => Ok(rust_gen::generate_arr(size, seed)),
+    }
+}
+
+/// Оценивает число π по доле случайных точек, попавших в четверть круга.
+/// Если `y_kind` не задан, координаты берутся парами подряд из одного потока генератора `x_kind`.
+/// Иначе координату y выдает генератор `y_kind` в независимом подпотоке
+/// с начальным значением [generator::substream_seed]. Вернет ошибку, если `size` равен нулю
+pub fn estimate_pi(
+    x_kind: GeneratorKind,
+    y_kind: Option<GeneratorKind>,
+    size: usize,
+    seed: u64,
+    options: &PiOptions,
+) -> Result<PiEstimate> {
+    if size == 0 {
+        bail!("Size should be positive");
+    }
+    let mut x_rng = x_kind.create(seed)?;
+
+    let estimate = match y_kind {
+        None => monte_carlo::estimate_pi(|| (x_rng.next_f64(), x_rng.next_f64()), size, options),
+        Some(y_kind) => {
+            let mut y_rng = y_kind.create(generator::substream_seed(seed, 1))?;
+            monte_carlo::estimate_pi(|| (x_rng.next_f64(), y_rng.next_f64()), size, options)
+        }
+    };
+
+    Ok(estimate.with_seed(seed))
+}
+
+/// Выборка закона распределения и ее сравнение с теоретическим законом
+#[derive(Serialize, Deserialize)]
+pub struct DistributionSample {
+    /// Закон распределения
+    pub distribution: Distribution,
+    /// Теоретическое мат. ожидание
+    pub mean: f64,
+    /// Теоретическая дисперсия
+    pub variance: f64,
+    /// Проверка по критерию Колмогорова-Смирнова, только для непрерывных законов
+    pub kolmogorov_smirnov: Option<KolmogorovSmirnov>,
+    /// Статистика выборки
+    pub stats: Stats,
+}
+
+/// Генерирует выборку закона `distribution` на основе выбранного равномерного генератора
+pub fn generate_distribution(
+    kind: GeneratorKind,
+    distribution: &Distribution,
+    size: usize,
+    seed: u64,
+) -> Result<Vec<f64>> {
+    Ok(distribution.sample_n(kind.create(seed)?.as_mut(), size))
+}
+
+/// Генерирует выборку закона `distribution`, считает ее статистику и сравнивает с теоретическим законом.
+/// Диапазон гистограммы берется из выборки
+pub fn distribution_stats(
+    kind: GeneratorKind,
+    distribution: &Distribution,
+    size: usize,
+    seed: u64,
+    options: &StatsOptions,
+) -> Result<DistributionSample> {
+    let sample = generate_distribution(kind, distribution, size, seed)?;
+    let options = StatsOptions {
+        range: None,
+        ..options.clone()
+    };
+
+    let kolmogorov_smirnov = if distribution.continuous() {
+        Some(criteria::kolmogorov_smirnov(&sample, |x| {
+            distribution.cdf(x)
+        }))
+    } else {
+        None
+    };
+    let stats = if distribution.discrete() {
+        // значения дискретных законов целые, гистограмма строится по целым числам
+        let sample = sample.iter().map(|x| *x as i64).collect::<Vec<_>>();
+        utils::stats(&sample, &options)?
+    } else {
+        utils::stats(&sample, &options)?
+    };
+
+    Ok(DistributionSample {
+        distribution: distribution.clone(),
+        mean: distribution.mean(),
+        variance: distribution.variance(),
+        kolmogorov_smirnov,
+        stats: stats.with_seed(seed),
+    })
+}
+
+/// Считает статистику потоково: выборка генерируется порциями по `chunk` значений и целиком в памяти не хранится.
+/// Порции генерируются и обрабатываются параллельно в `threads` потоках. Порция номер `i` берется
+/// из собственного подпотока с начальным значением [generator::substream_seed] (`seed`, `i`),
+/// поэтому результат зависит от `seed` и `chunk`, но не от количества потоков,
+/// и не совпадает с расчетом по [generate_int] / [generate_float] с тем же `seed`.
+/// `bins` - количество интервалов гистограммы и критерия хи-квадрат
+pub fn stream_stats(
+    kind: GeneratorKind,
+    size: usize,
+    seed: u64,
+    float: bool,
+    bins: usize,
+    chunk: usize,
+    threads: usize,
+) -> Result<OnlineStats> {
+    let (range, discrete) = if float {
+        (FLOAT_RANGE, false)
+    } else {
+        (RANGE, true)
+    };
+    let (chunk, threads) = (chunk.max(1), threads.max(1));
+
+    // пустой накопитель, копии которого заполняются в потоках
+    let empty = OnlineStats::new(range, bins, discrete)?;
+    let mut total = empty.clone();
+    let mut rest = size;
+    let mut index = 0;
+
+    while rest > 0 {
+        // по порции на каждый поток: номер подпотока и количество значений
+        let mut chunks = Vec::with_capacity(threads);
+        while chunks.len() < threads && rest > 0 {
+            let len = rest.min(chunk);
+            rest -= len;
+            chunks.push((kind.create(generator::substream_seed(seed, index))?, len));
+            index += 1;
+        }
+
+        let parts = thread::scope(|scope| {
+            let empty = &empty;
+            let handles = chunks
+                .into_iter()
+                .map(|(mut rng, len)| {
+                    scope.spawn(move || {
+                        let mut part = empty.clone();
+                        for _ in 0..len {
+                            part.push(if float {
+                                rng.next_f64() as f32 as f64
+                            } else {
+                                rng.next_in_range(0, utils::MAX) as f64
+                            });
+                        }
+                        part
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Stats thread panicked"))
+                .collect::<Vec<_>>()
+        });
+
+        // объединяем в порядке номеров порций, чтобы результат не зависел от планировщика
+        for part in &parts {
+            total.merge(part);
+        }
+    }
+
+    Ok(total)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// Раньше x и y брались из генераторов с соседними начальными значениями `seed` и `seed + 1`:
+    /// для MINSTD с seed = 3 получалось y(n) = 4/3 * x(n) mod m и ошибка в 16 стандартных ошибок
+    #[test]
+    fn pi_converges() {
+        let kinds = [
+            GeneratorKind::Rust,
+            GeneratorKind::Minstd,
+            GeneratorKind::NumRecipes,
+            GeneratorKind::Glibc,
+        ];
+
+        for kind in kinds.iter().cloned() {
+            for y_kind in [None, Some(kind), Some(GeneratorKind::Rust)]
+                .iter()
+                .cloned()
+            {
+                let estimate =
+                    estimate_pi(kind, y_kind, 1_000_000, 3, &PiOptions::default()).unwrap();
+
+                assert!(
+                    estimate.error < 4. * estimate.standard_error,
+                    "{:?}/{:?}: pi = {}, standard error = {}",
+                    kind,
+                    y_kind,
+                    estimate.pi,
+                    estimate.standard_error
+                );
+                assert!((estimate.pi - PI).abs() < 0.01);
+
+                // ошибка на последней контрольной точке меньше, чем на ранних
+                let early = &estimate.checkpoints[estimate.checkpoints.len() / 2];
+                assert!(early.standard_error > estimate.standard_error);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_pi_sample_is_rejected() {
+        assert!(estimate_pi(GeneratorKind::Rust, None, 0, 3, &PiOptions::default()).is_err());
+    }
+
+    /// Порции берутся из подпотоков по своим номерам, поэтому количество потоков на результат не влияет
+    #[test]
+    fn stream_stats_ignores_thread_count() {
+        let summary = |threads| {
+            let stats =
+                stream_stats(GeneratorKind::Minstd, 10_007, 3, false, 10, 1000, threads).unwrap();
+            assert_eq!(stats.size(), 10_007);
+            serde_json::to_string(&stats.summary(0.95)).unwrap()
+        };
+
+        let single = summary(1);
+        assert_eq!(summary(3), single);
+        assert_eq!(summary(16), single);
+    }
+}
@@ -1,13 +1,16 @@
 extern crate web_view;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use rnd_generator::integrand::{self, Integrand};
 use std::str::FromStr;
-use utils;
+use utils::distribution::Distribution;
+use utils::generator;
+use utils::generator::GeneratorKind;
+use utils::histogram::Binning;
+use utils::monte_carlo::{IntegrationMethod, PiOptions};
+use utils::StatsOptions;
 use web_view::*;
 
-mod lemer_gen;
-mod rust_gen;
-
 fn main() {
     let _res = web_view::builder()
         .title("Генерация псевдослучайных числовых последовательностей")
@@ -25,57 +28,278 @@ struct GenCmd {
     cmd: String,
     kind: String,
     size: usize,
+    /// Начальное значение генератора, если не задано - берется из часов
+    seed: Option<u64>,
+    /// Настройки расчета статистики
+    options: StatsOptions,
+    /// Генератор координаты y для расчета π в независимом подпотоке,
+    /// если не задан - координаты берутся парами из одного потока `kind`
+    y_kind: Option<String>,
+    /// Настройки расчета π
+    pi_options: PiOptions,
+    /// Функция для интегрирования методом Монте-Карло
+    integrand: Integrand,
+    /// Метод интегрирования
+    method: IntegrationMethod,
+    /// Размерность области интегрирования
+    dimensions: usize,
+    /// Закон распределения для команды `dist` в формате `имя:параметр:...`
+    distribution: Option<String>,
 }
 
 /// Parses string cmd and returns struct
 fn parse_cmd(arg: &str) -> Result<GenCmd> {
     let vec = arg.split('|').collect::<Vec<_>>();
-    assert!(vec.len() > 2, "cmd should have at least 3 fields");
+    if vec.len() < 3 {
+        bail!("Cmd should have at least 3 fields, got {:?}", arg);
+    }
+
+    // четвертое поле (seed) необязательное
+    let seed = match vec.get(3) {
+        Some(seed) if !seed.is_empty() => Some(u64::from_str(seed)?),
+        _ => None,
+    };
+    // страница показывает начальное значение числом JavaScript, большее значение исказится
+    if matches!(seed, Some(seed) if seed > generator::MAX_SEED) {
+        bail!("Seed should not exceed {}", generator::MAX_SEED);
+    }
+    // необязательное поле с номером `idx`, пустая строка равносильна отсутствию поля
+    let field = |idx: usize| vec.get(idx).cloned().filter(|field| !field.is_empty());
+
+    let cmd = vec[0].to_string();
+    let size = usize::from_str(vec[2])?;
+    let mut options = StatsOptions {
+        range: Some(rnd_generator::RANGE),
+        ..StatsOptions::default()
+    };
+    let mut y_kind = None;
+    let mut pi_options = PiOptions::default();
+    let mut integrand = Integrand::Sin;
+    let mut method = IntegrationMethod::SampleMean;
+    let mut dimensions = 1;
+    let mut distribution = None;
+
+    match cmd.as_str() {
+        "pi" => {
+            // pi|kind|size|seed|yKind|checkpoints|level
+            if size == 0 {
+                bail!("Size should be positive");
+            }
+            y_kind = field(4).map(|kind| kind.to_string());
+            if let Some(checkpoints) = field(5) {
+                pi_options.checkpoints = usize::from_str(checkpoints)?;
+            }
+            if let Some(level) = field(6) {
+                pi_options.confidence_level = StatsOptions::parse_confidence_level(level)?;
+            }
+        }
+        "integrate" => {
+            // integrate|kind|size|seed|integrand|method|dimensions|level
+            if let Some(name) = field(4) {
+                integrand = Integrand::from_str(name)?;
+            }
+            if let Some(name) = field(5) {
+                method = IntegrationMethod::from_str(name)?;
+            }
+            if let Some(count) = field(6) {
+                dimensions = usize::from_str(count)?;
+            }
+            if let Some(level) = field(7) {
+                options.confidence_level = StatsOptions::parse_confidence_level(level)?;
+            }
+        }
+        _ => {
+            // пятое поле (количество интервалов для критерия хи-квадрат) необязательное
+            if let Some(bins) = field(4) {
+                options.chi_squared_bins = usize::from_str(bins)?;
+            }
+            // шестое поле (максимальный лаг автокорреляции) необязательное
+            if let Some(max_lag) = field(5) {
+                options.max_lag = usize::from_str(max_lag)?;
+            }
+            // седьмое поле (количество интервалов гистограммы, sturges или fd) необязательное
+            if let Some(binning) = field(6) {
+                options.binning = Binning::from_str(binning)?;
+            }
+            // восьмое поле (доверительная вероятность) необязательное
+            if let Some(level) = field(7) {
+                options.confidence_level = StatsOptions::parse_confidence_level(level)?;
+            }
+            // девятое поле (уровни квантилей через запятую) необязательное
+            if let Some(quantiles) = field(8) {
+                options.quantiles = StatsOptions::parse_quantiles(quantiles)?;
+            }
+            // десятое поле (закон распределения) нужно только для dist
+            distribution = field(9).map(|spec| spec.to_string());
+        }
+    }
 
     Ok(GenCmd {
-        cmd: vec[0].to_string(),
+        cmd,
         kind: vec[1].to_string(),
-        size: usize::from_str(vec[2])?,
+        size,
+        seed,
+        options,
+        y_kind,
+        pi_options,
+        integrand,
+        method,
+        dimensions,
+        distribution,
     })
 }
 
 fn invoke_handler(wv: &mut WebView<usize>, arg: &str) -> WVResult {
     println!("Handled {:?}", arg);
 
-    let GenCmd { cmd, kind, size } = parse_cmd(arg).expect("Cmd should be defined");
-
-    if cmd == "gen" {
-        if kind == "genRust" {
-            // генерируем случайную величину
-            let data = rust_gen::generate(size);
-
-            let json_stats = serde_json::to_string(&utils::stats(&data)).unwrap();
-            let stats_js = format!("fillStats({})", json_stats);
-            println!("stats_js: {:?}", stats_js);
-            // вызываем функцию в Js для отрисовки UI
-            wv.eval(&stats_js)?;
-        } else if kind == "genLemer" {
-            // генерируем случайную величину
-            let data = lemer_gen::generate_arr_int(size, 100);
-
-            let json_stats = serde_json::to_string(&utils::stats(&data)).unwrap();
-            let stats_js = format!("fillStats({})", json_stats);
-            println!("stats_js: {:?}", stats_js);
-            // вызываем функцию в Js для отрисовки UI
-            wv.eval(&stats_js)?;
-        } else if kind == "pi" {
-            // генерируем случайную величину
-            let x_coords = lemer_gen::generate_arr(size);
-            let y_coords = lemer_gen::generate_arr(size);
-
-            let prob = utils::get_probability(&x_coords, &y_coords);
-            let pi_js = format!("fillPi({})", 4. * prob);
-
-            println!("pi_js: {:?}", pi_js);
-            wv.eval(&pi_js)?;
-        } else {
-            println!("Unknown kind {:?}", kind)
+    let GenCmd {
+        cmd,
+        kind,
+        size,
+        seed,
+        options,
+        y_kind,
+        pi_options,
+        integrand,
+        method,
+        dimensions,
+        distribution,
+    } = match parse_cmd(arg) {
+        Ok(cmd) => cmd,
+        Err(err) => {
+            println!("{}", err);
+            return Ok(());
         }
+    };
+
+    // запоминаем фактическое начальное значение, чтобы вернуть его пользователю
+    let seed = generator::resolve_seed(seed);
+
+    let kind = match GeneratorKind::from_str(&kind) {
+        Ok(kind) => kind,
+        Err(err) => {
+            println!("{}", err);
+            return Ok(());
+        }
+    };
+    // без генератора для y координаты берутся парами из одного потока
+    let y_kind = match y_kind.as_deref().map(GeneratorKind::from_str) {
+        Some(Ok(y_kind)) => Some(y_kind),
+        Some(Err(err)) => {
+            println!("{}", err);
+            return Ok(());
+        }
+        None => None,
+    };
+
+    if cmd == "gen" || cmd == "genFloat" {
+        // генерируем случайную величину выбранным генератором: целую или непрерывную в [0, 1)
+        let stats = if cmd == "gen" {
+            rnd_generator::generate_int(kind, size, seed)
+                .and_then(|data| utils::stats(&data, &options))
+        } else {
+            let options = StatsOptions {
+                range: Some(rnd_generator::FLOAT_RANGE),
+                ..options
+            };
+            rnd_generator::generate_float(kind, size, seed)
+                .and_then(|data| utils::stats(&data, &options))
+        };
+        let stats = match stats {
+            Ok(stats) => stats.with_seed(seed),
+            Err(err) => {
+                println!("{}", err);
+                return Ok(());
+            }
+        };
+
+        let json_stats = serde_json::to_string(&stats).unwrap();
+        let stats_js = format!("fillStats({})", json_stats);
+        println!("stats_js: {:?}", stats_js);
+        // вызываем функцию в Js для отрисовки UI
+        wv.eval(&stats_js)?;
+    } else if cmd == "dist" {
+        // генерируем выборку неравномерного закона на основе выбранного генератора
+        let distribution = match distribution.as_deref().map(Distribution::from_str) {
+            Some(Ok(distribution)) => distribution,
+            Some(Err(err)) => {
+                println!("{}", err);
+                return Ok(());
+            }
+            None => {
+                println!("Distribution is not set");
+                return Ok(());
+            }
+        };
+
+        let sample =
+            match rnd_generator::distribution_stats(kind, &distribution, size, seed, &options) {
+                Ok(sample) => sample,
+                Err(err) => {
+                    println!("{}", err);
+                    return Ok(());
+                }
+            };
+        let dist_js = format!(
+            "fillDistribution({})",
+            serde_json::to_string(&sample).unwrap()
+        );
+        println!("dist_js: {:?}", dist_js);
+        wv.eval(&dist_js)?;
+    } else if cmd == "pi" {
+        // оцениваем π по случайным точкам выбранных генераторов
+        let estimate = match rnd_generator::estimate_pi(kind, y_kind, size, seed, &pi_options) {
+            Ok(estimate) => estimate,
+            Err(err) => {
+                println!("{}", err);
+                return Ok(());
+            }
+        };
+        let pi_js = format!("fillPi({})", serde_json::to_string(&estimate).unwrap());
+
+        println!("pi_js: {:?}", pi_js);
+        wv.eval(&pi_js)?;
+    } else if cmd == "integrate" {
+        // интегрируем встроенную функцию по случайным точкам выбранного генератора
+        let result = match integrand::integrate(
+            kind,
+            integrand,
+            method,
+            dimensions,
+            size,
+            seed,
+            options.confidence_level,
+        ) {
+            Ok(result) => result,
+            Err(err) => {
+                println!("{}", err);
+                return Ok(());
+            }
+        };
+        let integral_js = format!("fillIntegral({})", serde_json::to_string(&result).unwrap());
+
+        println!("integral_js: {:?}", integral_js);
+        wv.eval(&integral_js)?;
+    } else if cmd == "cycle" {
+        // ищем период генератора, size - максимальное количество шагов
+        let cycle = match kind.find_cycle(seed, size) {
+            Ok(cycle) => cycle,
+            Err(err) => {
+                println!("{}", err);
+                return Ok(());
+            }
+        };
+        let cycle_js = format!(
+            "fillCycle({}, {}, {})",
+            serde_json::to_string(&cycle).unwrap(),
+            size,
+            seed
+        );
+
+        println!("cycle_js: {:?}", cycle_js);
+        wv.eval(&cycle_js)?;
+    } else {
+        println!("Unknown cmd {:?}", cmd)
     }
 
     Ok(())
@@ -1,14 +1,21 @@
 //! Встроенная в Rust генерация случайных чисел
 
-use rand::Rng;
+use utils::generator;
+use utils::generator::{Generator, RustRng};
 
-/// Генерирует массив случайных чисел в диапазоне [1,99] указанного размера (number)
-pub fn generate(size: usize) -> Vec<u32> {
-    let mut rng = rand::thread_rng();
+/// Генерирует массив случайных чисел в диапазоне [0, max) указанного размера (number)
+pub fn generate(size: usize, max: usize, seed: u64) -> Vec<u32> {
+    let mut rng = RustRng::from_seed(seed);
     let mut numbers = Vec::<u32>::with_capacity(size);
 
     for _ in 0..size {
-        numbers.push(rng.gen_range(1..100));
+        numbers.push(rng.next_in_range(0, max as u32));
     }
     numbers
 }
+
+/// Генерирует массив случайных чисел в диапазоне [0, 1) указанного размера (number)
+pub fn generate_arr(size: usize, seed: u64) -> Vec<f32> {
+    let mut rng = RustRng::from_seed(seed);
+    generator::generate_arr(&mut rng, size)
+}
@@ -0,0 +1,309 @@
+//! Статистические критерии согласия для проверки качества генераторов
+
+use crate::histogram::Histogram;
+use crate::special;
+use serde::{Deserialize, Serialize};
+
+/// Результат проверки по критерию хи-квадрат
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChiSquared {
+    /// Количество интервалов разбиения
+    pub bins: usize,
+    /// Значение статистики хи-квадрат
+    pub statistic: f64,
+    /// Число степеней свободы
+    pub degrees_of_freedom: usize,
+    /// p-значение: вероятность получить такое или большее отклонение от равномерного закона.
+    /// Маленькое значение (меньше 0.05) говорит о том, что выборка не равномерна
+    pub p_value: f64,
+}
+
+/// Критерий хи-квадрат равномерности по гистограмме выборки.
+/// Ожидаемое количество попаданий в интервал пропорционально его ширине
+/// (для целочисленной выборки - количеству целых чисел в нем)
+pub fn chi_squared_uniform(histogram: &Histogram) -> ChiSquared {
+    let edges = &histogram.edges;
+    let total_width = edges[edges.len() - 1] - edges[0];
+    let size = histogram.counts.iter().sum::<usize>() as f64;
+
+    let statistic = histogram
+        .counts
+        .iter()
+        .zip(edges.windows(2))
+        .map(|(observed, edge)| {
+            let expected = size * (edge[1] - edge[0]) / total_width;
+            if expected > 0. {
+                (*observed as f64 - expected).powi(2) / expected
+            } else {
+                0.
+            }
+        })
+        .sum::<f64>();
+
+    let bins = histogram.counts.len();
+    let degrees_of_freedom = bins.saturating_sub(1);
+
+    ChiSquared {
+        bins,
+        statistic,
+        degrees_of_freedom,
+        p_value: if degrees_of_freedom == 0 {
+            1.
+        } else {
+            special::chi_squared_sf(statistic, degrees_of_freedom)
+        },
+    }
+}
+
+/// Теоретическая функция распределения, с которой сравнивается выборка
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+#[serde(tag = "type")]
+pub enum Cdf {
+    /// Равномерный закон на [low, high)
+    Uniform { low: f64, high: f64 },
+    /// Нормальный закон с мат. ожиданием mean и среднеквадратичным отклонением deviation
+    Normal { mean: f64, deviation: f64 },
+    /// Показательный закон с интенсивностью rate
+    Exponential { rate: f64 },
+}
+
+impl Cdf {
+    /// Значение функции распределения в точке x
+    pub fn value(&self, x: f64) -> f64 {
+        match *self {
+            Cdf::Uniform { low, high } => ((x - low) / (high - low)).clamp(0., 1.),
+            Cdf::Normal { mean, deviation } => special::normal_cdf((x - mean) / deviation),
+            Cdf::Exponential { rate } => {
+                if x < 0. {
+                    0.
+                } else {
+                    1. - (-rate * x).exp()
+                }
+            }
+        }
+    }
+}
+
+/// Результат проверки по критерию Колмогорова-Смирнова
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct KolmogorovSmirnov {
+    /// Размер выборки
+    pub size: usize,
+    /// Статистика D: наибольшее отклонение эмпирической функции распределения от теоретической
+    pub statistic: f64,
+    /// p-значение, маленькое значение (меньше 0.05) говорит о том, что выборка не подчиняется закону
+    pub p_value: f64,
+}
+
+/// Критерий Колмогорова-Смирнова: сравнивает эмпирическую функцию распределения выборки
+/// с теоретической функцией распределения `cdf`. В качестве `cdf` можно передать [Cdf::value]
+/// или любую свою функцию
+pub fn kolmogorov_smirnov<F>(sample: &[f64], cdf: F) -> KolmogorovSmirnov
+where
+    F: Fn(f64) -> f64,
+{
+    let mut sorted = sample.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("Sample should not contain NaN"));
+
+    let size = sorted.len();
+    let n = size as f64;
+
+    // эмпирическая функция распределения ступенчатая, поэтому сравниваем
+    // теоретическую функцию с обоими краями каждой ступеньки
+    let statistic = sorted
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            let theoretical = cdf(*x);
+            let below = theoretical - i as f64 / n;
+            let above = (i + 1) as f64 / n - theoretical;
+            below.max(above)
+        })
+        .fold(0., f64::max);
+
+    // асимптотическое распределение Колмогорова с поправкой Стивенса для малых выборок
+    let sqrt_n = n.sqrt();
+    let lambda = (sqrt_n + 0.12 + 0.11 / sqrt_n) * statistic;
+
+    KolmogorovSmirnov {
+        size,
+        statistic,
+        p_value: if size == 0 {
+            1.
+        } else {
+            special::kolmogorov_sf(lambda)
+        },
+    }
+}
+
+/// Результат анализа корреляции между последовательными значениями выборки
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SerialCorrelation {
+    /// Коэффициенты автокорреляции для лагов 1..=max_lag (коррелограмма)
+    pub autocorrelation: Vec<f64>,
+    /// Граница значимости на уровне 0.95: коэффициенты, превышающие ее по модулю, значимо отличны от нуля
+    pub bound: f64,
+    /// Статистика Льюнга-Бокса Q по всем лагам
+    pub statistic: f64,
+    /// Число степеней свободы статистики Q
+    pub degrees_of_freedom: usize,
+    /// p-значение, маленькое значение (меньше 0.05) говорит о том, что значения выборки зависимы
+    pub p_value: f64,
+}
+
+/// Коэффициенты автокорреляции выборки для лагов 1..=max_lag.
+/// Коэффициент для лага k показывает, насколько значение зависит от значения, полученного k шагов назад
+pub fn autocorrelation(sample: &[f64], max_lag: usize) -> Vec<f64> {
+    let size = sample.len();
+    if size < 2 {
+        return vec![];
+    }
+
+    (1..=max_lag.min(size - 1))
+        .map(|lag| lag_coefficient(sample, lag))
+        .collect()
+}
+
+/// Коэффициент автокорреляции выборки для одного лага, для лага 0 равен 1
+fn lag_coefficient(sample: &[f64], lag: usize) -> f64 {
+    let mean = sample.iter().sum::<f64>() / sample.len() as f64;
+    let variance = sample.iter().map(|x| (x - mean).powi(2)).sum::<f64>();
+    if variance == 0. {
+        return 0.;
+    }
+
+    let covariance = sample
+        .iter()
+        .zip(&sample[lag..])
+        .map(|(x, y)| (x - mean) * (y - mean))
+        .sum::<f64>();
+    covariance / variance
+}
+
+/// Критерий серийной корреляции Льюнга-Бокса по лагам 1..=max_lag:
+/// для независимых значений статистика Q имеет распределение хи-квадрат с max_lag степенями свободы
+pub fn serial_correlation(sample: &[f64], max_lag: usize) -> SerialCorrelation {
+    let autocorrelation = autocorrelation(sample, max_lag);
+    let n = sample.len() as f64;
+
+    let statistic = n
+        * (n + 2.)
+        * autocorrelation
+            .iter()
+            .enumerate()
+            .map(|(idx, r)| r * r / (n - (idx + 1) as f64))
+            .sum::<f64>();
+    let degrees_of_freedom = autocorrelation.len();
+
+    SerialCorrelation {
+        bound: 1.96 / n.sqrt(),
+        statistic,
+        degrees_of_freedom,
+        p_value: if degrees_of_freedom == 0 {
+            1.
+        } else {
+            special::chi_squared_sf(statistic, degrees_of_freedom)
+        },
+        autocorrelation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{Generator, RustRng};
+
+    /// Гистограмма целых чисел 0..4 с количествами 5, 3, 2, 10: ожидается по 5 в каждом интервале,
+    /// χ² = 0 + 4/5 + 9/5 + 25/5 = 7.6 с 3 степенями свободы
+    #[test]
+    fn chi_squared_by_hand() {
+        let sample = [(0., 5), (1., 3), (2., 2), (3., 10)]
+            .iter()
+            .flat_map(|(x, count)| std::iter::repeat(*x).take(*count))
+            .collect::<Vec<_>>();
+        let histogram = Histogram::new(&sample, (0., 4.), 4, true).unwrap();
+        let chi_squared = chi_squared_uniform(&histogram);
+
+        assert_eq!(chi_squared.bins, 4);
+        assert_eq!(chi_squared.degrees_of_freedom, 3);
+        assert!((chi_squared.statistic - 7.6).abs() < 1e-12);
+        assert!((chi_squared.p_value - 0.055_043_936_378_878).abs() < 1e-9);
+    }
+
+    /// Интервалы разной ширины: ожидаемое количество пропорционально количеству целых чисел в интервале
+    #[test]
+    fn chi_squared_uneven_bins() {
+        let sample = (0..10).map(|x| x as f64).collect::<Vec<_>>();
+        let histogram = Histogram::new(&sample, (0., 10.), 3, true).unwrap();
+        let chi_squared = chi_squared_uniform(&histogram);
+
+        assert_eq!(histogram.edges, vec![0., 3., 6., 10.]);
+        assert!(chi_squared.statistic.abs() < 1e-12);
+        assert!((chi_squared.p_value - 1.).abs() < 1e-12);
+    }
+
+    #[test]
+    fn kolmogorov_smirnov_uniform() {
+        let mut rng = RustRng::from_seed(42);
+        let sample = (0..1000).map(|_| rng.next_f64()).collect::<Vec<_>>();
+
+        let uniform = Cdf::Uniform { low: 0., high: 1. };
+        let accepted = kolmogorov_smirnov(&sample, |x| uniform.value(x));
+        assert_eq!(accepted.size, 1000);
+        assert!(accepted.p_value > 0.05, "{:?}", accepted);
+
+        // выборка не доходит до 0.1 у сдвинутого закона, статистика D не меньше 0.1
+        let shifted = Cdf::Uniform {
+            low: 0.1,
+            high: 1.1,
+        };
+        let rejected = kolmogorov_smirnov(&sample, |x| shifted.value(x));
+        assert!(rejected.statistic >= 0.1, "{:?}", rejected);
+        assert!(rejected.p_value < 1e-6, "{:?}", rejected);
+    }
+
+    /// Для выборки из одного значения 0.5 и равномерного закона D = max(0.5, 1 - 0.5)
+    #[test]
+    fn kolmogorov_smirnov_statistic_by_hand() {
+        let uniform = Cdf::Uniform { low: 0., high: 1. };
+        let result = kolmogorov_smirnov(&[0.5], |x| uniform.value(x));
+        assert!((result.statistic - 0.5).abs() < 1e-15);
+
+        let result = kolmogorov_smirnov(&[0.1, 0.2], |x| uniform.value(x));
+        assert!((result.statistic - 0.8).abs() < 1e-15);
+    }
+
+    #[test]
+    fn autocorrelation_extremes() {
+        let sample = [3., 1., 4., 1., 5., 9., 2., 6.];
+        assert!((lag_coefficient(&sample, 0) - 1.).abs() < 1e-15);
+
+        // для чередующейся последовательности r(1) = -(n - 1) / n, r(2) = (n - 2) / n
+        let alternating = (0..100)
+            .map(|i| if i % 2 == 0 { 1. } else { -1. })
+            .collect::<Vec<_>>();
+        let r = autocorrelation(&alternating, 2);
+        assert!((r[0] + 0.99).abs() < 1e-12, "{:?}", r);
+        assert!((r[1] - 0.98).abs() < 1e-12, "{:?}", r);
+
+        // у постоянной выборки корреляции нет, лаги ограничены размером выборки
+        assert_eq!(autocorrelation(&[2., 2., 2.], 5), vec![0., 0.]);
+    }
+
+    /// Выборка 1..=5: r(1) = 4/10, r(2) = -1/10, Q = 5 * 7 * (0.16 / 4 + 0.01 / 3),
+    /// для двух степеней свободы p = e^(-Q/2)
+    #[test]
+    fn ljung_box_by_hand() {
+        let result = serial_correlation(&[1., 2., 3., 4., 5.], 2);
+
+        assert_eq!(result.autocorrelation.len(), 2);
+        assert!((result.autocorrelation[0] - 0.4).abs() < 1e-15);
+        assert!((result.autocorrelation[1] + 0.1).abs() < 1e-15);
+        assert_eq!(result.degrees_of_freedom, 2);
+
+        let q = 35. * (0.16 / 4. + 0.01 / 3.);
+        assert!((result.statistic - q).abs() < 1e-12);
+        assert!((result.p_value - (-q / 2.).exp()).abs() < 1e-12);
+        assert!((result.bound - 1.96 / 5_f64.sqrt()).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,624 @@
+//! Случайные величины с неравномерными законами распределения, построенные
+//! на любом равномерном генераторе [Generator]
+
+use crate::generator::Generator;
+use crate::special;
+use anyhow::{anyhow, bail};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// Начиная с этого мат. ожидания закон Пуассона моделируется методом PTRS,
+/// для меньших - перемножением равномерных величин
+const POISSON_PTRS_MEAN: f64 = 30.;
+
+/// Закон распределения случайной величины
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum Distribution {
+    /// Равномерный закон на [low, high)
+    Uniform { low: f64, high: f64 },
+    /// Показательный закон с интенсивностью rate
+    Exponential { rate: f64 },
+    /// Нормальный закон с мат. ожиданием mean и среднеквадратичным отклонением deviation
+    Normal { mean: f64, deviation: f64 },
+    /// Закон Пуассона с мат. ожиданием mean
+    Poisson { mean: f64 },
+    /// Закон Эрланга: сумма shape показательных величин с интенсивностью rate
+    Erlang { shape: u32, rate: f64 },
+    /// Гамма-распределение с параметром формы shape и масштаба scale
+    Gamma { shape: f64, scale: f64 },
+    /// Гиперэкспоненциальный закон: с вероятностью probabilities\[i\] показательная величина
+    /// с интенсивностью rates\[i\]
+    Hyperexponential {
+        probabilities: Vec<f64>,
+        rates: Vec<f64>,
+    },
+    /// Закон Бернулли: 1 с вероятностью p, иначе 0
+    Bernoulli { p: f64 },
+    /// Геометрический закон: номер первого успешного испытания (1, 2, ...) при вероятности успеха p
+    Geometric { p: f64 },
+    /// Дискретный закон, заданный значениями и их весами (веса не обязаны давать в сумме 1)
+    Empirical { values: Vec<f64>, weights: Vec<f64> },
+}
+
+impl Distribution {
+    /// Проверяет параметры закона: все параметры конечны, иначе выборка состояла бы из NaN
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let finite = |params: &[f64]| params.iter().all(|param| param.is_finite());
+        let valid = match self {
+            Distribution::Uniform { low, high } => finite(&[*low, *high]) && low < high,
+            Distribution::Exponential { rate } => rate.is_finite() && *rate > 0.,
+            Distribution::Normal { mean, deviation } => {
+                finite(&[*mean, *deviation]) && *deviation >= 0.
+            }
+            Distribution::Poisson { mean } => mean.is_finite() && *mean >= 0.,
+            Distribution::Erlang { shape, rate } => *shape > 0 && rate.is_finite() && *rate > 0.,
+            Distribution::Gamma { shape, scale } => {
+                finite(&[*shape, *scale]) && *shape > 0. && *scale > 0.
+            }
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => {
+                !rates.is_empty()
+                    && probabilities.len() == rates.len()
+                    && probabilities.iter().all(|p| *p >= 0.)
+                    && (probabilities.iter().sum::<f64>() - 1.).abs() < 1e-9
+                    && finite(rates)
+                    && rates.iter().all(|rate| *rate > 0.)
+            }
+            Distribution::Bernoulli { p } => (0. ..=1.).contains(p),
+            Distribution::Geometric { p } => *p > 0. && *p <= 1.,
+            Distribution::Empirical { values, weights } => {
+                !values.is_empty()
+                    && values.len() == weights.len()
+                    && finite(values)
+                    && finite(weights)
+                    && weights.iter().all(|weight| *weight >= 0.)
+                    && weights.iter().sum::<f64>() > 0.
+            }
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(anyhow!("Bad distribution parameters {:?}", self))
+        }
+    }
+
+    /// Закон дискретный: значения - целые числа
+    pub fn discrete(&self) -> bool {
+        matches!(
+            self,
+            Distribution::Poisson { .. }
+                | Distribution::Bernoulli { .. }
+                | Distribution::Geometric { .. }
+        )
+    }
+
+    /// Закон непрерывный, к нему применим критерий Колмогорова-Смирнова
+    pub fn continuous(&self) -> bool {
+        !self.discrete() && !matches!(self, Distribution::Empirical { .. })
+    }
+
+    /// Генерирует случайное значение
+    pub fn sample(&self, rng: &mut dyn Generator) -> f64 {
+        match self {
+            Distribution::Uniform { low, high } => low + (high - low) * rng.next_f64(),
+            Distribution::Exponential { rate } => exponential(rng) / rate,
+            Distribution::Normal { mean, deviation } => mean + deviation * standard_normal(rng),
+            Distribution::Poisson { mean } => poisson(rng, *mean),
+            Distribution::Erlang { shape, rate } => {
+                (0..*shape).map(|_| exponential(rng)).sum::<f64>() / rate
+            }
+            Distribution::Gamma { shape, scale } => gamma(rng, *shape) * scale,
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => exponential(rng) / rates[choose(rng, probabilities.iter().copied())],
+            Distribution::Bernoulli { p } => {
+                if rng.next_f64() < *p {
+                    1.
+                } else {
+                    0.
+                }
+            }
+            Distribution::Geometric { p } => {
+                if *p >= 1. {
+                    1.
+                } else {
+                    // обратная функция распределения, 1 - u лежит в (0, 1]
+                    ((1. - rng.next_f64()).ln() / (1. - p).ln()).ceil().max(1.)
+                }
+            }
+            Distribution::Empirical { values, weights } => {
+                values[choose(rng, weights.iter().copied())]
+            }
+        }
+    }
+
+    /// Генерирует выборку размера `size`
+    pub fn sample_n(&self, rng: &mut dyn Generator, size: usize) -> Vec<f64> {
+        (0..size).map(|_| self.sample(rng)).collect()
+    }
+
+    /// Теоретическое мат. ожидание
+    pub fn mean(&self) -> f64 {
+        match self {
+            Distribution::Uniform { low, high } => (low + high) / 2.,
+            Distribution::Exponential { rate } => 1. / rate,
+            Distribution::Normal { mean, .. } => *mean,
+            Distribution::Poisson { mean } => *mean,
+            Distribution::Erlang { shape, rate } => *shape as f64 / rate,
+            Distribution::Gamma { shape, scale } => shape * scale,
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => probabilities
+                .iter()
+                .zip(rates)
+                .map(|(p, rate)| p / rate)
+                .sum(),
+            Distribution::Bernoulli { p } => *p,
+            Distribution::Geometric { p } => 1. / p,
+            Distribution::Empirical { values, weights } => {
+                let total = weights.iter().sum::<f64>();
+                values.iter().zip(weights).map(|(x, w)| x * w).sum::<f64>() / total
+            }
+        }
+    }
+
+    /// Теоретическая дисперсия
+    pub fn variance(&self) -> f64 {
+        match self {
+            Distribution::Uniform { low, high } => (high - low).powi(2) / 12.,
+            Distribution::Exponential { rate } => 1. / (rate * rate),
+            Distribution::Normal { deviation, .. } => deviation * deviation,
+            Distribution::Poisson { mean } => *mean,
+            Distribution::Erlang { shape, rate } => *shape as f64 / (rate * rate),
+            Distribution::Gamma { shape, scale } => shape * scale * scale,
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => {
+                // второй момент показательного закона равен 2 / rate^2
+                let second_moment = probabilities
+                    .iter()
+                    .zip(rates)
+                    .map(|(p, rate)| 2. * p / (rate * rate))
+                    .sum::<f64>();
+                second_moment - self.mean().powi(2)
+            }
+            Distribution::Bernoulli { p } => p * (1. - p),
+            Distribution::Geometric { p } => (1. - p) / (p * p),
+            Distribution::Empirical { values, weights } => {
+                let total = weights.iter().sum::<f64>();
+                let mean = self.mean();
+                values
+                    .iter()
+                    .zip(weights)
+                    .map(|(x, w)| (x - mean).powi(2) * w)
+                    .sum::<f64>()
+                    / total
+            }
+        }
+    }
+
+    /// Значение функции распределения в точке x
+    pub fn cdf(&self, x: f64) -> f64 {
+        match self {
+            Distribution::Uniform { low, high } => ((x - low) / (high - low)).clamp(0., 1.),
+            Distribution::Exponential { rate } => {
+                if x < 0. {
+                    0.
+                } else {
+                    1. - (-rate * x).exp()
+                }
+            }
+            Distribution::Normal { mean, deviation } => special::normal_cdf((x - mean) / deviation),
+            Distribution::Poisson { mean } => {
+                if x < 0. {
+                    0.
+                } else if *mean == 0. {
+                    1.
+                } else {
+                    // P(X <= k) = Q(k + 1, mean)
+                    special::gamma_q(x.floor() + 1., *mean)
+                }
+            }
+            Distribution::Erlang { shape, rate } => {
+                if x <= 0. {
+                    0.
+                } else {
+                    special::gamma_p(*shape as f64, rate * x)
+                }
+            }
+            Distribution::Gamma { shape, scale } => {
+                if x <= 0. {
+                    0.
+                } else {
+                    special::gamma_p(*shape, x / scale)
+                }
+            }
+            Distribution::Hyperexponential {
+                probabilities,
+                rates,
+            } => {
+                if x < 0. {
+                    0.
+                } else {
+                    1. - probabilities
+                        .iter()
+                        .zip(rates)
+                        .map(|(p, rate)| p * (-rate * x).exp())
+                        .sum::<f64>()
+                }
+            }
+            Distribution::Bernoulli { p } => {
+                if x < 0. {
+                    0.
+                } else if x < 1. {
+                    1. - p
+                } else {
+                    1.
+                }
+            }
+            Distribution::Geometric { p } => {
+                if x < 1. {
+                    0.
+                } else {
+                    1. - (1. - p).powf(x.floor())
+                }
+            }
+            Distribution::Empirical { values, weights } => {
+                let total = weights.iter().sum::<f64>();
+                values
+                    .iter()
+                    .zip(weights)
+                    .filter(|(value, _)| **value <= x)
+                    .map(|(_, weight)| weight)
+                    .sum::<f64>()
+                    / total
+            }
+        }
+    }
+}
+
+impl FromStr for Distribution {
+    type Err = anyhow::Error;
+
+    /// Разбирает закон в формате `имя:параметр:параметр`, например `normal:0:1`, `erlang:3:0.5`,
+    /// `hyperexponential:0.3,0.7:1,5` (вероятности и интенсивности ветвей)
+    /// или `empirical:1,2,2,5` (значения с равными весами, повтор значения увеличивает его вес)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut fields = s.split(':');
+        let name = fields.next().unwrap_or_default();
+        let params = fields.collect::<Vec<_>>();
+
+        let number = |idx: usize| -> anyhow::Result<f64> {
+            let param = params
+                .get(idx)
+                .ok_or_else(|| anyhow!("Distribution {:?} requires more parameters", s))?;
+            f64::from_str(param).map_err(|err| anyhow!("Bad parameter {:?}: {}", param, err))
+        };
+
+        let list = |idx: usize| -> anyhow::Result<Vec<f64>> {
+            params
+                .get(idx)
+                .cloned()
+                .unwrap_or_default()
+                .split(',')
+                .map(|value| {
+                    f64::from_str(value.trim())
+                        .map_err(|err| anyhow!("Bad value {:?}: {}", value, err))
+                })
+                .collect()
+        };
+
+        let distribution = match name {
+            "uniform" => Distribution::Uniform {
+                low: number(0)?,
+                high: number(1)?,
+            },
+            "exponential" => Distribution::Exponential { rate: number(0)? },
+            "normal" => Distribution::Normal {
+                mean: number(0)?,
+                deviation: number(1)?,
+            },
+            "poisson" => Distribution::Poisson { mean: number(0)? },
+            "erlang" => Distribution::Erlang {
+                shape: u32::from_str(params.first().cloned().unwrap_or_default())
+                    .map_err(|err| anyhow!("Bad Erlang shape: {}", err))?,
+                rate: number(1)?,
+            },
+            "gamma" => Distribution::Gamma {
+                shape: number(0)?,
+                scale: number(1)?,
+            },
+            "hyperexponential" => Distribution::Hyperexponential {
+                probabilities: list(0)?,
+                rates: list(1)?,
+            },
+            "bernoulli" => Distribution::Bernoulli { p: number(0)? },
+            "geometric" => Distribution::Geometric { p: number(0)? },
+            "empirical" => {
+                let values = list(0)?;
+                Distribution::Empirical {
+                    weights: vec![1.; values.len()],
+                    values,
+                }
+            }
+            _ => bail!(
+                "Unknown distribution {:?}, expected uniform, exponential, normal, poisson, \
+                 erlang, gamma, hyperexponential, bernoulli, geometric or empirical",
+                name
+            ),
+        };
+
+        distribution.validate()?;
+        Ok(distribution)
+    }
+}
+
+/// Индекс, выбранный с вероятностью, пропорциональной его весу (веса не обязаны давать в сумме 1)
+pub fn choose<I>(rng: &mut dyn Generator, weights: I) -> usize
+where
+    I: IntoIterator<Item = f64>,
+    I::IntoIter: Clone,
+{
+    let weights = weights.into_iter();
+    let mut target = rng.next_f64() * weights.clone().sum::<f64>();
+    let mut last = 0;
+    for (idx, weight) in weights.enumerate() {
+        if target < weight {
+            return idx;
+        }
+        target -= weight;
+        last = idx;
+    }
+    // из-за ошибок округления target может остаться чуть больше нуля
+    last
+}
+
+/// Показательная величина с интенсивностью 1 методом обратной функции
+fn exponential(rng: &mut dyn Generator) -> f64 {
+    // 1 - u лежит в (0, 1], логарифм конечен
+    -(1. - rng.next_f64()).ln()
+}
+
+/// Стандартная нормальная величина преобразованием Бокса-Мюллера
+pub fn standard_normal(rng: &mut dyn Generator) -> f64 {
+    let radius = (-2. * (1. - rng.next_f64()).ln()).sqrt();
+    let angle = 2. * PI * rng.next_f64();
+    radius * angle.cos()
+}
+
+/// Величина по закону Пуассона: для малых мат. ожиданий перемножением равномерных величин (метод Кнута),
+/// для больших - методом PTRS (W. Hörmann, 1993), время работы которого не зависит от мат. ожидания
+fn poisson(rng: &mut dyn Generator, mean: f64) -> f64 {
+    if mean < POISSON_PTRS_MEAN {
+        let limit = (-mean).exp();
+        let mut product = rng.next_f64();
+        let mut count = 0.;
+        while product > limit {
+            product *= rng.next_f64();
+            count += 1.;
+        }
+        return count;
+    }
+
+    let sqrt_mean = mean.sqrt();
+    let b = 0.931 + 2.53 * sqrt_mean;
+    let a = -0.059 + 0.02483 * b;
+    let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+    let v_r = 0.9277 - 3.6224 / (b - 2.);
+
+    loop {
+        let u = rng.next_f64() - 0.5;
+        let v = rng.next_f64();
+        let us = 0.5 - u.abs();
+        let k = ((2. * a / us + b) * u + mean + 0.43).floor();
+
+        if us >= 0.07 && v <= v_r {
+            return k;
+        }
+        if k < 0. || (us < 0.013 && v > us) {
+            continue;
+        }
+        if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln()
+            <= -mean + k * mean.ln() - special::ln_gamma(k + 1.)
+        {
+            return k;
+        }
+    }
+}
+
+/// Гамма-величина с масштабом 1 методом Марсальи-Цанга
+fn gamma(rng: &mut dyn Generator, shape: f64) -> f64 {
+    if shape <= 0. {
+        return f64::NAN;
+    }
+    if shape < 1. {
+        // Г(shape) = Г(shape + 1) * U^(1 / shape)
+        return gamma(rng, shape + 1.) * rng.next_f64().powf(1. / shape);
+    }
+
+    let d = shape - 1. / 3.;
+    let c = 1. / (9. * d).sqrt();
+    loop {
+        let x = standard_normal(rng);
+        let v = (1. + c * x).powi(3);
+        if v <= 0. {
+            continue;
+        }
+        let u = 1. - rng.next_f64();
+        if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+            return d * v;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::criteria;
+    use crate::generator::RustRng;
+
+    /// По одному закону каждого вида, для Пуассона и гамма-распределения обе ветви алгоритма
+    fn laws() -> Vec<Distribution> {
+        vec![
+            Distribution::Uniform { low: -2., high: 3. },
+            Distribution::Exponential { rate: 0.5 },
+            Distribution::Normal {
+                mean: 10.,
+                deviation: 2.,
+            },
+            Distribution::Poisson { mean: 4. },
+            Distribution::Poisson { mean: 100. },
+            Distribution::Erlang { shape: 3, rate: 2. },
+            Distribution::Gamma {
+                shape: 0.5,
+                scale: 2.,
+            },
+            Distribution::Gamma {
+                shape: 4.5,
+                scale: 0.5,
+            },
+            Distribution::Hyperexponential {
+                probabilities: vec![0.3, 0.7],
+                rates: vec![1., 5.],
+            },
+            Distribution::Bernoulli { p: 0.3 },
+            Distribution::Geometric { p: 0.25 },
+            Distribution::Empirical {
+                values: vec![1., 2., 5.],
+                weights: vec![1., 2., 1.],
+            },
+        ]
+    }
+
+    /// Выборочные мат. ожидание и дисперсия совпадают с теоретическими:
+    /// мат. ожидание в пределах пяти стандартных ошибок, дисперсия с точностью 5%
+    #[test]
+    fn sample_moments() {
+        let size = 200_000;
+        for distribution in laws() {
+            let mut rng = RustRng::from_seed(7);
+            let sample = distribution.sample_n(&mut rng, size);
+
+            let mean = sample.iter().sum::<f64>() / size as f64;
+            let variance =
+                sample.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (size - 1) as f64;
+            let standard_error = (distribution.variance() / size as f64).sqrt();
+
+            assert!(
+                (mean - distribution.mean()).abs() < 5. * standard_error,
+                "{:?}: mean {}, expected {}",
+                distribution,
+                mean,
+                distribution.mean()
+            );
+            assert!(
+                (variance / distribution.variance() - 1.).abs() < 0.05,
+                "{:?}: variance {}, expected {}",
+                distribution,
+                variance,
+                distribution.variance()
+            );
+            if distribution.discrete() {
+                assert!(sample.iter().all(|x| x.fract() == 0.));
+            }
+        }
+    }
+
+    /// Выборки непрерывных законов проходят критерий Колмогорова-Смирнова со своей функцией распределения
+    #[test]
+    fn continuous_laws_pass_kolmogorov_smirnov() {
+        for distribution in laws().into_iter().filter(Distribution::continuous) {
+            let mut rng = RustRng::from_seed(11);
+            let sample = distribution.sample_n(&mut rng, 20_000);
+            let result = criteria::kolmogorov_smirnov(&sample, |x| distribution.cdf(x));
+            assert!(
+                result.p_value > 0.01,
+                "{:?}: D = {}, p = {}",
+                distribution,
+                result.statistic,
+                result.p_value
+            );
+        }
+    }
+
+    #[test]
+    fn parse_all_laws() {
+        let parsed = [
+            "uniform:-2:3",
+            "exponential:0.5",
+            "normal:10:2",
+            "poisson:4",
+            "poisson:100",
+            "erlang:3:2",
+            "gamma:0.5:2",
+            "gamma:4.5:0.5",
+            "hyperexponential:0.3,0.7:1,5",
+            "bernoulli:0.3",
+            "geometric:0.25",
+        ]
+        .iter()
+        .map(|s| Distribution::from_str(s).unwrap())
+        .collect::<Vec<_>>();
+        assert_eq!(parsed[..], laws()[..parsed.len()]);
+
+        // повтор значения увеличивает его вес
+        assert_eq!(
+            Distribution::from_str("empirical:1,2,2,5").unwrap(),
+            Distribution::Empirical {
+                values: vec![1., 2., 2., 5.],
+                weights: vec![1., 1., 1., 1.],
+            }
+        );
+    }
+
+    #[test]
+    fn bad_laws_are_rejected() {
+        let bad = [
+            "",
+            "cauchy:0:1",
+            "normal:0",
+            "exponential:abc",
+            "uniform:1:1",
+            "exponential:0",
+            "normal:0:-1",
+            "poisson:-1",
+            "erlang:0:1",
+            "erlang:1.5:1",
+            "gamma:0:1",
+            "hyperexponential:0.3,0.6:1,2",
+            "hyperexponential:0.5,0.5:1",
+            "hyperexponential:0.5,0.5:1,0",
+            "bernoulli:1.5",
+            "geometric:0",
+            "empirical:",
+            "normal:nan:1",
+            "uniform:0:inf",
+            "exponential:inf",
+            "poisson:inf",
+            "gamma:1:nan",
+            "erlang:2:inf",
+            "hyperexponential:0.5,0.5:1,inf",
+            "empirical:1,inf",
+        ];
+        for s in bad.iter() {
+            assert!(Distribution::from_str(s).is_err(), "{:?} accepted", s);
+        }
+
+        for distribution in laws() {
+            assert!(distribution.validate().is_ok(), "{:?}", distribution);
+        }
+        let zero_weights = Distribution::Empirical {
+            values: vec![1., 2.],
+            weights: vec![0., 0.],
+        };
+        assert!(zero_weights.validate().is_err());
+    }
+}
@@ -0,0 +1,137 @@
+//! Поиск периода псевдослучайной последовательности алгоритмом Брента
+
+use crate::generator::Generator;
+use serde::{Deserialize, Serialize};
+
+/// Цикл последовательности состояний генератора
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub struct Cycle {
+    /// Длина предпериода: сколько состояний проходит генератор до того, как попадает в цикл
+    pub tail: usize,
+    /// Длина периода: через сколько шагов состояния начинают повторяться
+    pub period: usize,
+}
+
+/// Ищет цикл в последовательности `start, step(start), step(step(start)), ...` алгоритмом Брента.
+/// Хранит только два состояния, поэтому подходит для генераторов с большим периодом.
+/// Если за `max_iterations` вызовов `step` цикл не найден, вернет None
+pub fn find_cycle<S, F>(start: &S, mut step: F, max_iterations: usize) -> Option<Cycle>
+where
+    S: Clone + PartialEq,
+    F: FnMut(&S) -> S,
+{
+    let mut iterations = 0;
+
+    // ищем длину периода: черепаха ждет в точке, заяц уходит вперед на степени двойки
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = start.clone();
+    let mut hare = step(start);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+
+        iterations += 1;
+        if iterations >= max_iterations {
+            return None;
+        }
+    }
+
+    // ищем начало цикла: заяц отстает от черепахи ровно на длину периода
+    let mut tortoise = start.clone();
+    let mut hare = start.clone();
+    for _ in 0..period {
+        hare = step(&hare);
+    }
+
+    let mut tail = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        tail += 1;
+
+        iterations += 1;
+        if iterations >= max_iterations {
+            return None;
+        }
+    }
+
+    Some(Cycle { tail, period })
+}
+
+/// Ищет период генератора, начиная с его текущего состояния
+pub fn find_generator_cycle<G>(rng: &G, max_iterations: usize) -> Option<Cycle>
+where
+    G: Generator + Clone + PartialEq,
+{
+    find_cycle(
+        rng,
+        |rng| {
+            let mut next = rng.clone();
+            next.next_u32();
+            next
+        },
+        max_iterations,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::{GeneratorKind, Lemer, LemerParams};
+
+    /// m = 16, a = 5, c = 1 удовлетворяют теореме Халла-Добелла, поэтому период полный
+    #[test]
+    fn full_period_lcg() {
+        let kind = GeneratorKind::Lcg(LemerParams::new(5, 1, 16).unwrap());
+        for seed in 0..16 {
+            assert_eq!(
+                kind.find_cycle(seed, 1000).unwrap(),
+                Some(Cycle {
+                    tail: 0,
+                    period: 16
+                })
+            );
+        }
+    }
+
+    /// x(n+1) = 2 x(n) mod 12 из 1: 1, 2, 4, 8, 4, 8, ... - предпериод 2, период 2
+    #[test]
+    fn lcg_with_tail() {
+        let rng = Lemer::new(LemerParams::new(2, 0, 12).unwrap(), 1).unwrap();
+        assert_eq!(
+            find_generator_cycle(&rng, 1000),
+            Some(Cycle { tail: 2, period: 2 })
+        );
+
+        // 0, 1, 2, 3, 4, 2, 3, 4, ... - предпериод 2, период 3
+        let step = |x: &u32| if *x < 4 { x + 1 } else { 2 };
+        assert_eq!(
+            find_cycle(&0, step, 1000),
+            Some(Cycle { tail: 2, period: 3 })
+        );
+    }
+
+    #[test]
+    fn iteration_cap() {
+        let kind = GeneratorKind::Lcg(LemerParams::new(5, 1, 16).unwrap());
+        assert_eq!(kind.find_cycle(0, 10).unwrap(), None);
+        assert_eq!(GeneratorKind::Minstd.find_cycle(1, 10_000).unwrap(), None);
+
+        // предпериод длиннее ограничения на количество шагов
+        let step = |x: &u64| if *x < 100 { x + 1 } else { 99 };
+        assert_eq!(find_cycle(&0, step, 50), None);
+        assert_eq!(
+            find_cycle(&0, step, 1000),
+            Some(Cycle {
+                tail: 99,
+                period: 2
+            })
+        );
+    }
+}
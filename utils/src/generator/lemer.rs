@@ -0,0 +1,215 @@
+//! Генерация псевдослучайной последовательности методом Лемера (линейный конгруэнтный метод)
+//!
+//! Следующее значение вычисляется как `x(n+1) = (a * x(n) + c) mod m`. Все вычисления выполняются
+//! в u128, поэтому переполнения невозможны при любых параметрах с модулем до 2^64.
+
+use crate::generator::Generator;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+
+/// Количество значащих бит в мантиссе f64
+const F64_BITS: u32 = 53;
+
+/// Параметры линейного конгруэнтного генератора. При чтении из JSON модуль проверяется так же,
+/// как в [LemerParams::new]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+#[serde(try_from = "RawLemerParams")]
+pub struct LemerParams {
+    /// Множитель (a)
+    pub multiplier: u64,
+    /// Приращение (c)
+    pub increment: u64,
+    /// Модуль (m)
+    pub modulus: u64,
+}
+
+/// Параметры в том виде, в котором они пришли из JSON, до проверки
+#[derive(Deserialize)]
+struct RawLemerParams {
+    multiplier: u64,
+    increment: u64,
+    modulus: u64,
+}
+
+impl TryFrom<RawLemerParams> for LemerParams {
+    type Error = anyhow::Error;
+
+    fn try_from(raw: RawLemerParams) -> Result<Self> {
+        LemerParams::new(raw.multiplier, raw.increment, raw.modulus)
+    }
+}
+
+impl LemerParams {
+    /// Параметры генератора с множителем `multiplier`, приращением `increment` и модулем `modulus`,
+    /// вернет ошибку для нулевого модуля
+    pub fn new(multiplier: u64, increment: u64, modulus: u64) -> Result<Self> {
+        if modulus == 0 {
+            bail!("Modulus should be positive");
+        }
+
+        Ok(LemerParams {
+            multiplier,
+            increment,
+            modulus,
+        })
+    }
+
+    /// Классические параметры из учебника: a = 25173, c = 13849, m = 2^16
+    pub const CLASSIC: LemerParams = LemerParams {
+        multiplier: 25173,
+        increment: 13849,
+        modulus: 1 << 16,
+    };
+
+    /// Минимальный стандарт Парка и Миллера (MINSTD): a = 16807, c = 0, m = 2^31 - 1
+    pub const MINSTD: LemerParams = LemerParams {
+        multiplier: 16807,
+        increment: 0,
+        modulus: (1 << 31) - 1,
+    };
+
+    /// Параметры из книги Numerical Recipes: a = 1664525, c = 1013904223, m = 2^32
+    pub const NUMERICAL_RECIPES: LemerParams = LemerParams {
+        multiplier: 1664525,
+        increment: 1013904223,
+        modulus: 1 << 32,
+    };
+
+    /// Параметры функции rand() из glibc: a = 1103515245, c = 12345, m = 2^31
+    pub const GLIBC: LemerParams = LemerParams {
+        multiplier: 1103515245,
+        increment: 12345,
+        modulus: 1 << 31,
+    };
+}
+
+/// Генератор псевдослучайных чисел методом Лемера
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lemer {
+    params: LemerParams,
+    prev: u64,
+}
+
+impl Lemer {
+    /// Создаем генератор псевдослучайных чисел с заданными параметрами для указанного начального значения,
+    /// вернет ошибку для нулевого модуля
+    pub fn new(params: LemerParams, seed: u64) -> Result<Self> {
+        if params.modulus == 0 {
+            bail!("Modulus should be positive");
+        }
+
+        let mut prev = seed % params.modulus;
+        // мультипликативный генератор (c = 0) навсегда застрянет в нуле
+        if params.increment == 0 && prev == 0 {
+            prev = 1;
+        }
+
+        Ok(Lemer { params, prev })
+    }
+
+    /// Параметры генератора
+    pub fn params(&self) -> LemerParams {
+        self.params
+    }
+
+    /// Вернет следующее внутреннее состояние генератора в диапазоне [0, m)
+    pub fn next_state(&mut self) -> u64 {
+        let LemerParams {
+            multiplier,
+            increment,
+            modulus,
+        } = self.params;
+
+        // вычисляем следующее значение, в u128 произведение не переполняется
+        let next = (multiplier as u128 * self.prev as u128 + increment as u128) % modulus as u128;
+        // обновляем внутренне состояние генератора
+        self.prev = next as u64;
+        self.prev
+    }
+}
+
+impl Generator for Lemer {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_f64() * (u32::MAX as f64 + 1.)) as u32
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        let state = self.next_state();
+        let modulus = self.params.modulus;
+        if modulus <= 1 << F64_BITS {
+            // уменьшаем число до [0: 1), состояние и модуль представимы в f64 точно
+            state as f64 / modulus as f64
+        } else {
+            // для модуля больше 2^53 частное могло бы округлиться до 1,
+            // поэтому берем старшие 53 бита дроби state / m
+            let bits = ((state as u128) << F64_BITS) / modulus as u128;
+            bits as f64 / (1u64 << F64_BITS) as f64
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Первые состояния генератора с параметрами `params` и начальным значением `seed`
+    fn states(params: LemerParams, seed: u64) -> Vec<u64> {
+        let mut rng = Lemer::new(params, seed).unwrap();
+        (0..3).map(|_| rng.next_state()).collect()
+    }
+
+    #[test]
+    fn presets_match_reference() {
+        assert_eq!(
+            states(LemerParams::MINSTD, 1),
+            vec![16807, 282475249, 1622650073]
+        );
+        assert_eq!(
+            states(LemerParams::NUMERICAL_RECIPES, 0),
+            vec![1013904223, 1196435762, 3519870697]
+        );
+        assert_eq!(
+            states(LemerParams::GLIBC, 1),
+            vec![1103527590, 377401575, 662824084]
+        );
+    }
+
+    /// При m = 2^32 произведение a * x + c не помещается в u64, но считается в u128 без переполнения
+    #[test]
+    fn no_overflow_at_max_modulus() {
+        let max = (1 << 32) - 1;
+        let params = LemerParams::new(max, max, 1 << 32).unwrap();
+
+        assert_eq!(states(params, max), vec![0, max, 0]);
+    }
+
+    /// При модуле больше 2^53 частное (m - 2) / m в f64 округлялось бы до 1
+    #[test]
+    fn float_below_one_at_max_modulus() {
+        let params = LemerParams::new(1, 0, u64::MAX).unwrap();
+        let mut rng = Lemer::new(params, u64::MAX - 1).unwrap();
+
+        assert!(rng.next_f64() < 1.);
+        assert!(rng.next_in_range(0, 10) < 10);
+        assert_eq!(rng.next_u32(), u32::MAX);
+    }
+
+    #[test]
+    fn zero_modulus_is_rejected() {
+        let params = LemerParams {
+            modulus: 0,
+            ..LemerParams::CLASSIC
+        };
+        assert!(Lemer::new(params, 1).is_err());
+        assert!(LemerParams::new(1, 1, 0).is_err());
+
+        let json = r#"{"multiplier": 1, "increment": 1, "modulus": 0}"#;
+        assert!(serde_json::from_str::<LemerParams>(json).is_err());
+        let json = r#"{"multiplier": 5, "increment": 1, "modulus": 16}"#;
+        assert_eq!(
+            serde_json::from_str::<LemerParams>(json).unwrap(),
+            LemerParams::new(5, 1, 16).unwrap()
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Генераторы псевдослучайных чисел с общим интерфейсом
+
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Поиск периода генератора
+pub mod cycle;
+/// Генератор методом Лемера
+pub mod lemer;
+/// Адаптер для встроенного в Rust генератора
+pub mod rust;
+
+pub use cycle::Cycle;
+pub use lemer::{Lemer, LemerParams};
+pub use rust::RustRng;
+
+/// Общий интерфейс генератора псевдослучайных чисел. Debug нужен, чтобы генератор
+/// можно было хранить в структурах с выводом состояния для отладки
+pub trait Generator: Debug {
+    /// Вернет следующее случайное 32-битное число и обновит внутреннее состояние генератора
+    fn next_u32(&mut self) -> u32;
+
+    /// Вернет случайное число в диапазоне [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        self.next_u32() as f64 / (u32::MAX as f64 + 1.)
+    }
+
+    /// Вернет случайное целое число в диапазоне [low, high)
+    fn next_in_range(&mut self, low: u32, high: u32) -> u32 {
+        assert!(low < high, "low should be less than high");
+        low + (self.next_f64() * (high - low) as f64) as u32
+    }
+
+    /// Заполняет срез случайными числами в диапазоне [0, 1)
+    fn fill(&mut self, dest: &mut [f64]) {
+        for x in dest {
+            *x = self.next_f64();
+        }
+    }
+}
+
+impl<G: Generator + ?Sized> Generator for Box<G> {
+    fn next_u32(&mut self) -> u32 {
+        (**self).next_u32()
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        (**self).next_f64()
+    }
+
+    fn next_in_range(&mut self, low: u32, high: u32) -> u32 {
+        (**self).next_in_range(low, high)
+    }
+
+    fn fill(&mut self, dest: &mut [f64]) {
+        (**self).fill(dest)
+    }
+}
+
+/// Тип генератора, выбираемый пользователем
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Default)]
+pub enum GeneratorKind {
+    /// Встроенный генератор Rust
+    #[default]
+    #[serde(rename = "genRust")]
+    Rust,
+    /// Генератор методом Лемера с классическими параметрами
+    #[serde(rename = "genLemer")]
+    Lemer,
+    /// Генератор Лемера с параметрами MINSTD
+    #[serde(rename = "genMinstd")]
+    Minstd,
+    /// Генератор Лемера с параметрами из Numerical Recipes
+    #[serde(rename = "genNumRecipes")]
+    NumRecipes,
+    /// Генератор Лемера с параметрами glibc rand()
+    #[serde(rename = "genGlibc")]
+    Glibc,
+    /// Генератор Лемера с параметрами, заданными пользователем
+    #[serde(rename = "genLcg")]
+    Lcg(LemerParams),
+}
+
+impl GeneratorKind {
+    /// Создает генератор выбранного типа с заданным начальным значением,
+    /// вернет ошибку для недопустимых параметров генератора Лемера
+    pub fn create(self, seed: u64) -> anyhow::Result<Box<dyn Generator + Send>> {
+        Ok(match self.lemer_params() {
+            Some(params) => Box::new(Lemer::new(params, seed)?),
+            None => Box::new(RustRng::from_seed(seed)),
+        })
+    }
+
+    /// Ищет период генератора для заданного начального значения, перебирая не более `max_iterations` состояний
+    pub fn find_cycle(self, seed: u64, max_iterations: usize) -> anyhow::Result<Option<Cycle>> {
+        Ok(match self.lemer_params() {
+            Some(params) => cycle::find_generator_cycle(&Lemer::new(params, seed)?, max_iterations),
+            None => cycle::find_generator_cycle(&RustRng::from_seed(seed), max_iterations),
+        })
+    }
+
+    /// Параметры генератора Лемера, для остальных генераторов вернет None
+    pub fn lemer_params(self) -> Option<LemerParams> {
+        match self {
+            GeneratorKind::Rust => None,
+            GeneratorKind::Lemer => Some(LemerParams::CLASSIC),
+            GeneratorKind::Minstd => Some(LemerParams::MINSTD),
+            GeneratorKind::NumRecipes => Some(LemerParams::NUMERICAL_RECIPES),
+            GeneratorKind::Glibc => Some(LemerParams::GLIBC),
+            GeneratorKind::Lcg(params) => Some(params),
+        }
+    }
+}
+
+impl FromStr for GeneratorKind {
+    type Err = anyhow::Error;
+
+    /// Разбирает имя генератора, для пользовательских параметров формат `genLcg:a:c:m`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "genRust" => Ok(GeneratorKind::Rust),
+            "genLemer" => Ok(GeneratorKind::Lemer),
+            "genMinstd" => Ok(GeneratorKind::Minstd),
+            "genNumRecipes" => Ok(GeneratorKind::NumRecipes),
+            "genGlibc" => Ok(GeneratorKind::Glibc),
+            _ if s.starts_with("genLcg:") => {
+                let fields = s.split(':').skip(1).collect::<Vec<_>>();
+                if fields.len() != 3 {
+                    return Err(anyhow::anyhow!("Expected genLcg:a:c:m, got {:?}", s));
+                }
+
+                let params = LemerParams::new(
+                    u64::from_str(fields[0])?,
+                    u64::from_str(fields[1])?,
+                    u64::from_str(fields[2])?,
+                )?;
+
+                Ok(GeneratorKind::Lcg(params))
+            }
+            _ => Err(anyhow::anyhow!("Unknown generator kind {:?}", s)),
+        }
+    }
+}
+
+/// Наибольшее начальное значение, которое число JavaScript (f64) хранит без округления: 2^53 - 1.
+/// Графический интерфейс показывает начальное значение как число, большие значения в нем исказятся
+pub const MAX_SEED: u64 = (1 << 53) - 1;
+
+/// Вернет заданное пользователем начальное значение генератора, а если оно не задано - возьмет его из часов.
+/// Полученное значение нужно вернуть пользователю, чтобы он мог воспроизвести результат,
+/// поэтому значение из часов обрезается до [MAX_SEED]
+pub fn resolve_seed(seed: Option<u64>) -> u64 {
+    seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("System time is before UNIX epoch")
+            .as_nanos() as u64
+            & MAX_SEED
+    })
+}
+
+/// Начальное значение для независимого подпотока номер `index`, полученное из `seed` перемешиванием SplitMix64.
+/// Соседние начальные значения (`seed`, `seed + 1`) у генераторов Лемера дают сильно коррелированные
+/// последовательности, а перемешанные значения попадают в далекие друг от друга точки периода
+pub fn substream_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9E37_79B9_7F4A_7C15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Генерирует массив случайных чисел в диапазоне [0, max) указанного размера (size)
+pub fn generate_arr_int(rng: &mut dyn Generator, size: usize, max: u32) -> Vec<u32> {
+    (0..size).map(|_| rng.next_in_range(0, max)).collect()
+}
+
+/// Генерирует массив случайных чисел в диапазоне [0, 1) указанного размера (size)
+pub fn generate_arr(rng: &mut dyn Generator, size: usize) -> Vec<f32> {
+    (0..size).map(|_| rng.next_f64() as f32).collect()
+}
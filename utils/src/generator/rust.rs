@@ -0,0 +1,39 @@
+//! Встроенная в Rust генерация случайных чисел
+
+use crate::generator::Generator;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Адаптер встроенного генератора Rust к общему интерфейсу генераторов
+#[derive(Clone, Debug, PartialEq)]
+pub struct RustRng {
+    rng: StdRng,
+}
+
+impl RustRng {
+    /// Создаем генератор, инициализированный энтропией операционной системы
+    pub fn new() -> Self {
+        RustRng {
+            rng: StdRng::from_entropy(),
+        }
+    }
+
+    /// Создаем генератор для указанного начального значения
+    pub fn from_seed(seed: u64) -> Self {
+        RustRng {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Default for RustRng {
+    fn default() -> Self {
+        RustRng::new()
+    }
+}
+
+impl Generator for RustRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+}
@@ -0,0 +1,291 @@
+//! Построение гистограммы выборки с произвольным диапазоном и количеством интервалов
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Максимальное количество интервалов гистограммы, как при автоматическом выборе,
+/// так и заданное пользователем: больше интервалов не поместится на графике, а память под них выделяется сразу
+pub const MAX_BINS: usize = 10_000;
+
+/// Способ выбора количества интервалов гистограммы
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Binning {
+    /// Заданное количество интервалов
+    Fixed(usize),
+    /// Правило Стёрджеса: 1 + log2(n) интервалов
+    Sturges,
+    /// Правило Фридмана-Диакониса: ширина интервала 2 * IQR / n^(1/3)
+    FreedmanDiaconis,
+}
+
+impl FromStr for Binning {
+    type Err = anyhow::Error;
+
+    /// Разбирает количество интервалов (не больше [MAX_BINS]), `sturges` или `fd`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sturges" => Ok(Binning::Sturges),
+            "fd" => Ok(Binning::FreedmanDiaconis),
+            _ => match usize::from_str(s) {
+                Ok(bins) if bins > 0 && bins <= MAX_BINS => Ok(Binning::Fixed(bins)),
+                _ => Err(anyhow!(
+                    "Bad binning {:?}, expected number in [1, {}], sturges or fd",
+                    s,
+                    MAX_BINS
+                )),
+            },
+        }
+    }
+}
+
+/// Гистограмма: количество значений выборки, попавших в каждый интервал
+#[derive(Clone, Debug)]
+pub struct Histogram {
+    /// Границы интервалов, i-й интервал - [edges[i], edges[i+1])
+    pub edges: Vec<f64>,
+    /// Количество значений в каждом интервале
+    pub counts: Vec<usize>,
+    /// Размер выборки, включая значения вне диапазона
+    pub size: usize,
+    /// Выборка целочисленная, границы интервалов - целые числа
+    pub discrete: bool,
+}
+
+impl Histogram {
+    /// Строит гистограмму на диапазоне [low, high) из `bins` интервалов одинаковой ширины.
+    /// Для целочисленной выборки (`discrete`) границы округляются до целых, чтобы каждое число
+    /// попадало ровно в один интервал. Значения вне диапазона не учитываются
+    pub fn new(sample: &[f64], range: (f64, f64), bins: usize, discrete: bool) -> Result<Self> {
+        let mut histogram = Histogram::empty(range, bins, discrete)?;
+        for x in sample {
+            histogram.push(*x);
+        }
+        Ok(histogram)
+    }
+
+    /// Пустая гистограмма на диапазоне [low, high) из `bins` интервалов, значения добавляются через [Histogram::push].
+    /// Количество интервалов ограничено [MAX_BINS]. Вернет ошибку, если диапазон пуст или не конечен
+    pub fn empty((low, high): (f64, f64), bins: usize, discrete: bool) -> Result<Self> {
+        if !low.is_finite() || !high.is_finite() || low >= high {
+            bail!(
+                "Bad histogram range [{}, {}), low should be less than high",
+                low,
+                high
+            );
+        }
+
+        // в диапазоне уже единицы целых чисел все равно будет один интервал
+        let bins = if discrete {
+            bins.clamp(1, ((high - low) as usize).clamp(1, MAX_BINS))
+        } else {
+            bins.clamp(1, MAX_BINS)
+        };
+
+        let edges = (0..=bins)
+            .map(|i| {
+                let edge = low + (high - low) * i as f64 / bins as f64;
+                if discrete {
+                    edge.floor()
+                } else {
+                    edge
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(Histogram {
+            edges,
+            counts: vec![0; bins],
+            size: 0,
+            discrete,
+        })
+    }
+
+    /// Добавляет значение в гистограмму
+    pub fn push(&mut self, x: f64) {
+        self.size += 1;
+
+        let bins = self.counts.len();
+        let (low, high) = (self.edges[0], self.edges[bins]);
+        if x < low || x > high || (self.discrete && x >= high) {
+            return;
+        }
+        // номер интервала: количество верхних границ не больше числа
+        let bin = self.edges[1..].partition_point(|edge| *edge <= x);
+        self.counts[bin.min(bins - 1)] += 1;
+    }
+
+    /// Добавляет к гистограмме значения другой гистограммы с теми же интервалами
+    pub fn merge(&mut self, other: &Histogram) {
+        assert_eq!(self.edges, other.edges, "histograms should have same edges");
+
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.size += other.size;
+    }
+
+    /// Строит гистограмму, выбирая количество интервалов способом `binning`
+    pub fn with_binning(
+        sample: &[f64],
+        range: (f64, f64),
+        binning: Binning,
+        discrete: bool,
+    ) -> Result<Self> {
+        Histogram::new(sample, range, bins_count(sample, range, binning), discrete)
+    }
+
+    /// Относительная частота попадания в каждый интервал (дифференциальная функция распределения)
+    pub fn density(&self) -> Vec<f64> {
+        self.counts
+            .iter()
+            .map(|count| *count as f64 / self.size.max(1) as f64)
+            .collect()
+    }
+
+    /// Накопленная частота на правой границе каждого интервала (интегральная функция распределения)
+    pub fn distribution(&self) -> Vec<f64> {
+        let mut sum = 0.;
+        self.density()
+            .iter()
+            .map(|prob| {
+                // сумма вероятности растет для каждого элемета
+                sum += prob;
+                sum
+            })
+            .collect()
+    }
+}
+
+/// Диапазон значений выборки [min, max], для целочисленной выборки правая граница max + 1 не включается
+pub fn sample_range(sample: &[f64], discrete: bool) -> (f64, f64) {
+    let min = sample.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = sample.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+
+    if sample.is_empty() {
+        (0., 1.)
+    } else if discrete {
+        (min, max + 1.)
+    } else if min == max {
+        (min, min + 1.)
+    } else {
+        (min, max)
+    }
+}
+
+/// Количество интервалов для выборки согласно правилу `binning`
+pub fn bins_count(sample: &[f64], (low, high): (f64, f64), binning: Binning) -> usize {
+    let size = sample.len().max(1) as f64;
+    let sturges = (size.log2().ceil() as usize + 1).max(1);
+
+    match binning {
+        Binning::Fixed(bins) => bins.clamp(1, MAX_BINS),
+        Binning::Sturges => sturges,
+        Binning::FreedmanDiaconis => {
+            let mut sorted = sample.to_vec();
+            sorted.sort_by(|a, b| a.partial_cmp(b).expect("Sample should not contain NaN"));
+            let iqr = quantile_sorted(&sorted, 0.75) - quantile_sorted(&sorted, 0.25);
+            if iqr <= 0. {
+                return sturges;
+            }
+            let width = 2. * iqr / size.cbrt();
+            (((high - low) / width).ceil() as usize).clamp(1, MAX_BINS)
+        }
+    }
+}
+
+/// Квантиль уровня `p` отсортированной выборки с линейной интерполяцией
+pub(crate) fn quantile_sorted(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return f64::NAN;
+    }
+    let pos = p.clamp(0., 1.) * (sorted.len() - 1) as f64;
+    let idx = pos.floor() as usize;
+    let frac = pos - idx as f64;
+    match sorted.get(idx + 1) {
+        Some(next) => sorted[idx] + (next - sorted[idx]) * frac,
+        None => sorted[idx],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bad_range_is_rejected() {
+        assert!(Histogram::empty((1., 1.), 10, false).is_err());
+        assert!(Histogram::empty((2., 1.), 10, true).is_err());
+        assert!(Histogram::empty((f64::NAN, 1.), 10, false).is_err());
+        assert!(Histogram::empty((0., f64::INFINITY), 10, false).is_err());
+    }
+
+    /// Количество интервалов от пользователя ограничено, иначе память под них выделялась бы сразу
+    #[test]
+    fn huge_bin_count() {
+        assert_eq!(
+            Binning::from_str("10000").unwrap(),
+            Binning::Fixed(MAX_BINS)
+        );
+        assert!(Binning::from_str("10001").is_err());
+        assert!(Binning::from_str("0").is_err());
+
+        let histogram = Histogram::empty((0., 1.), usize::MAX, false).unwrap();
+        assert_eq!(histogram.counts.len(), MAX_BINS);
+        let histogram = Histogram::empty((0., 1e9), usize::MAX, true).unwrap();
+        assert_eq!(histogram.counts.len(), MAX_BINS);
+    }
+
+    /// Диапазон целочисленной выборки уже единицы: раньше clamp(1, 0) паниковал
+    #[test]
+    fn narrow_discrete_range() {
+        let histogram = Histogram::new(&[0., 0., 0.], (0., 0.5), 10, true).unwrap();
+        assert_eq!(histogram.counts.len(), 1);
+
+        // постоянная выборка
+        for discrete in [false, true].iter().cloned() {
+            let sample = [7.; 20];
+            let range = sample_range(&sample, discrete);
+            let histogram = Histogram::new(&sample, range, 10, discrete).unwrap();
+            assert_eq!(histogram.counts.iter().sum::<usize>(), 20);
+        }
+    }
+
+    #[test]
+    fn values_above_hundred() {
+        let sample = [100., 150., 250., 999.];
+        let histogram = Histogram::new(&sample, sample_range(&sample, true), 10, true).unwrap();
+
+        assert_eq!(histogram.edges[0], 100.);
+        assert_eq!(histogram.edges[10], 1000.);
+        assert_eq!(histogram.counts.iter().sum::<usize>(), 4);
+        assert_eq!(histogram.counts[0], 2);
+        assert_eq!(histogram.counts[9], 1);
+    }
+
+    #[test]
+    fn automatic_bins() {
+        let sample = (0..1000).map(|x| x as f64).collect::<Vec<_>>();
+        // Стёрджес: ceil(log2(1000)) + 1
+        assert_eq!(bins_count(&sample, (0., 1000.), Binning::Sturges), 11);
+        assert_eq!(bins_count(&[], (0., 1.), Binning::Sturges), 1);
+        assert_eq!(bins_count(&[5.], (0., 1.), Binning::Sturges), 1);
+
+        // Фридман-Диаконис: IQR = 749.25 - 249.75, ширина 2 * 499.5 / 10 = 99.9
+        assert_eq!(
+            bins_count(&sample, (0., 1000.), Binning::FreedmanDiaconis),
+            11
+        );
+        // IQR = 0 - как у Стёрджеса, очень узкие интервалы ограничены сверху
+        assert_eq!(bins_count(&[3.; 8], (3., 4.), Binning::FreedmanDiaconis), 4);
+        assert_eq!(
+            bins_count(&sample, (0., 1e12), Binning::FreedmanDiaconis),
+            MAX_BINS
+        );
+        assert_eq!(bins_count(&sample, (0., 1.), Binning::Fixed(0)), 1);
+        assert_eq!(
+            bins_count(&sample, (0., 1.), Binning::Fixed(usize::MAX)),
+            MAX_BINS
+        );
+    }
+}
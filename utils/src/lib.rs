@@ -2,79 +2,319 @@
 
 pub use serde::{Deserialize, Serialize};
 
+/// Добавляет результату с полем `seed: Option<u64>` метод `with_seed`.
+/// Объявлен до модулей, чтобы его можно было вызвать в каждом из них
+macro_rules! impl_with_seed {
+    ($ty:ty) => {
+        impl $ty {
+            /// Запоминает начальное значение генератора, чтобы результат можно было воспроизвести
+            pub fn with_seed(mut self, seed: u64) -> Self {
+                self.seed = Some(seed);
+                self
+            }
+        }
+    };
+}
+
+/// Статистические критерии согласия
+pub mod criteria;
+/// Законы распределения случайных величин
+pub mod distribution;
+/// Генераторы псевдослучайных чисел
+pub mod generator;
+/// Гистограмма выборки
+pub mod histogram;
+/// Метод Монте-Карло: оценка числа π и интегрирование
+pub mod monte_carlo;
+/// Потоковый расчет статистики
+pub mod online;
+/// Вывод результатов в текстовом виде
+pub mod report;
+/// Специальные функции
+pub mod special;
+
+use crate::criteria::{ChiSquared, SerialCorrelation};
+use crate::histogram::{Binning, Histogram};
+use anyhow::{anyhow, Result};
+use std::str::FromStr;
+
+/// Целочисленные случайные величины по умолчанию генерируются в диапазоне [0, MAX)
+pub const MAX: u32 = 100;
+
+/// Числовой тип, из значений которого состоит выборка
+pub trait Sample: Copy {
+    /// Значения целочисленные: гистограмма строится по целым границам
+    const DISCRETE: bool;
+
+    /// Значение в виде f64, все расчеты выполняются в f64
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_sample {
+    ($discrete:expr, $($ty:ty),*) => {
+        $(
+            impl Sample for $ty {
+                const DISCRETE: bool = $discrete;
+
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_sample!(true, u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+impl_sample!(false, f32, f64);
+
+/// Настройки расчета статистики
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct StatsOptions {
+    /// Количество интервалов для критерия хи-квадрат
+    pub chi_squared_bins: usize,
+    /// Максимальный лаг для расчета автокорреляции
+    pub max_lag: usize,
+    /// Диапазон значений [low, high), если не задан - берется из выборки.
+    /// Для проверки равномерности нужно указывать диапазон генератора, а не выборки
+    pub range: Option<(f64, f64)>,
+    /// Способ выбора количества интервалов гистограммы
+    pub binning: Binning,
+    /// Уровни квантилей, которые нужно рассчитать, каждый в [0, 1]
+    pub quantiles: Vec<f64>,
+    /// Доверительная вероятность для интервала мат. ожидания, например 0.95
+    pub confidence_level: f64,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        StatsOptions {
+            chi_squared_bins: 10,
+            max_lag: 20,
+            range: None,
+            binning: Binning::Sturges,
+            quantiles: vec![0.05, 0.25, 0.75, 0.95],
+            confidence_level: 0.95,
+        }
+    }
+}
+
+/// Квантиль выборки
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct Quantile {
+    /// Уровень квантиля
+    pub level: f64,
+    /// Значение, меньше которого доля `level` выборки
+    pub value: f64,
+}
+
+/// Доверительный интервал для мат. ожидания
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct ConfidenceInterval {
+    /// Доверительная вероятность
+    pub level: f64,
+    /// Нижняя граница
+    pub low: f64,
+    /// Верхняя граница
+    pub high: f64,
+}
+
+impl StatsOptions {
+    /// Разбирает уровни квантилей, перечисленные через запятую, например `0.05,0.5,0.95`
+    pub fn parse_quantiles(s: &str) -> anyhow::Result<Vec<f64>> {
+        s.split(',')
+            .map(|level| match f64::from_str(level.trim()) {
+                Ok(level) if (0. ..=1.).contains(&level) => Ok(level),
+                _ => Err(anyhow!(
+                    "Bad quantile level {:?}, expected number in [0, 1]",
+                    level
+                )),
+            })
+            .collect()
+    }
+
+    /// Разбирает доверительную вероятность, число в (0, 1)
+    pub fn parse_confidence_level(s: &str) -> anyhow::Result<f64> {
+        match f64::from_str(s) {
+            Ok(level) if level > 0. && level < 1. => Ok(level),
+            _ => Err(anyhow!(
+                "Bad confidence level {:?}, expected number in (0, 1)",
+                s
+            )),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Stats {
+    /// Начальное значение генератора, с которым получена выборка
+    seed: Option<u64>,
     /// Размер выборки
     size: usize,
     /// Мат ожидание
-    expectation: f32,
-    /// Дисперсия
-    dispersion: f32,
+    expectation: f64,
+    /// Дисперсия генеральной совокупности (сумма квадратов отклонений, деленная на n)
+    dispersion: f64,
+    /// Несмещенная выборочная дисперсия (сумма квадратов отклонений, деленная на n - 1)
+    sample_dispersion: f64,
     /// Среднеквардатичное отклонение
-    deviation: f32,
-    /// Дифференциальной функция распределения
-    density: Vec<f32>,
-    /// Интегральаня функция распределения
-    distribution: Vec<f32>,
-}
-
-/// Расчитывает стохастические величины: мат. ожидание, дисперсию и тп
-pub fn stats(vec: &[u32]) -> Stats {
-    let expectation = expectation(&vec);
-    let dispersion = dispersion(&vec, expectation);
-    let density = distribution_density(vec);
-    Stats {
+    deviation: f64,
+    /// Выборочное среднеквардатичное отклонение, корень из несмещенной дисперсии
+    sample_deviation: f64,
+    /// Коэффициент асимметрии
+    skewness: f64,
+    /// Коэффициент эксцесса (для нормального закона равен 0)
+    kurtosis: f64,
+    /// Минимальное значение
+    min: f64,
+    /// Максимальное значение
+    max: f64,
+    /// Медиана
+    median: f64,
+    /// Квантили заданных уровней
+    quantiles: Vec<Quantile>,
+    /// Доверительный интервал для мат. ожидания
+    confidence_interval: ConfidenceInterval,
+    /// Границы интервалов гистограммы, i-й интервал - [edges[i], edges[i+1])
+    edges: Vec<f64>,
+    /// Дифференциальной функция распределения: частота попадания в каждый интервал
+    density: Vec<f64>,
+    /// Интегральаня функция распределения на правой границе каждого интервала
+    distribution: Vec<f64>,
+    /// Проверка равномерности по критерию хи-квадрат
+    chi_squared: ChiSquared,
+    /// Автокорреляция и проверка независимости последовательных значений
+    serial_correlation: SerialCorrelation,
+}
+
+/// Расчитывает стохастические величины: мат. ожидание, дисперсию и тп.
+/// Выборка может состоять как из целых чисел, так и из непрерывных величин (например, в [0, 1)).
+/// Все расчеты выполняются в f64. Вернет ошибку, если диапазон гистограммы пуст или не конечен
+pub fn stats<T: Sample>(vec: &[T], options: &StatsOptions) -> Result<Stats> {
+    let discrete = T::DISCRETE;
+    let sample = vec.iter().map(|x| x.to_f64()).collect::<Vec<_>>();
+
+    let expectation = expectation(vec);
+    let squared_deviations = squared_deviations(&sample, expectation);
+    let dispersion = dispersion(squared_deviations, sample.len());
+    let sample_dispersion = sample_dispersion(squared_deviations, sample.len());
+
+    let range = options
+        .range
+        .unwrap_or_else(|| histogram::sample_range(&sample, discrete));
+    let histogram = Histogram::with_binning(&sample, range, options.binning, discrete)?;
+    let chi_histogram = Histogram::new(&sample, range, options.chi_squared_bins, discrete)?;
+
+    let mut sorted = sample.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("Sample should not contain NaN"));
+    let quantile = |level: f64| histogram::quantile_sorted(&sorted, level);
+
+    Ok(Stats {
+        seed: None,
         size: vec.len(),
         expectation,
         dispersion,
+        sample_dispersion,
         deviation: deviation(dispersion),
-        density: Vec::from(density.clone()),
-        distribution: Vec::from(distribution(&density)),
-    }
+        sample_deviation: deviation(sample_dispersion),
+        skewness: skewness(&sample, expectation, dispersion),
+        kurtosis: kurtosis(&sample, expectation, dispersion),
+        min: sorted.first().cloned().unwrap_or(f64::NAN),
+        max: sorted.last().cloned().unwrap_or(f64::NAN),
+        median: quantile(0.5),
+        quantiles: options
+            .quantiles
+            .iter()
+            .map(|level| Quantile {
+                level: *level,
+                value: quantile(*level),
+            })
+            .collect(),
+        confidence_interval: confidence_interval(
+            vec.len(),
+            expectation,
+            sample_dispersion,
+            options.confidence_level,
+        ),
+        density: histogram.density(),
+        distribution: histogram.distribution(),
+        edges: histogram.edges,
+        chi_squared: criteria::chi_squared_uniform(&chi_histogram),
+        serial_correlation: criteria::serial_correlation(&sample, options.max_lag),
+    })
 }
 
+impl_with_seed!(Stats);
+
 /// Мат ожидание случайной величины
-pub fn expectation(vec: &[u32]) -> f32 {
-    vec.iter().sum::<u32>() as f32 / vec.len() as f32
+pub fn expectation<T: Sample>(vec: &[T]) -> f64 {
+    vec.iter().map(|x| x.to_f64()).sum::<f64>() / vec.len() as f64
+}
+
+/// Сумма квадратов отклонений от мат. ожидания.
+/// Двухпроходный алгоритм с поправкой на ошибку округления мат. ожидания
+/// (сумма отклонений в точной арифметике равна нулю), устойчив при большом мат. ожидании и малом разбросе
+fn squared_deviations(sample: &[f64], expectation: f64) -> f64 {
+    let (sum, sum_squares) = sample.iter().fold((0., 0.), |(sum, sum_squares), x| {
+        let deviation = x - expectation;
+        (sum + deviation, sum_squares + deviation * deviation)
+    });
+    sum_squares - sum * sum / sample.len() as f64
+}
+
+/// Дисперсия генеральной совокупности
+fn dispersion(squared_deviations: f64, size: usize) -> f64 {
+    squared_deviations / size as f64
 }
 
-/// Дисперсия случайной величины
-fn dispersion(vec: &[u32], expectation: f32) -> f32 {
-    vec.iter()
-        .map(|x| {
-            // случайная число - мат ожидание в квадрате деленное на размер выборки
-            (*x as f32 - expectation).powi(2) / vec.len() as f32
-        })
-        .sum()
+/// Несмещенная выборочная дисперсия, для выборки из одного значения не определена
+fn sample_dispersion(squared_deviations: f64, size: usize) -> f64 {
+    if size < 2 {
+        f64::NAN
+    } else {
+        squared_deviations / (size - 1) as f64
+    }
 }
 
 /// Среднеквардатичное отклонение случайной величины
-fn deviation(dispersion: f32) -> f32 {
+fn deviation(dispersion: f64) -> f64 {
     dispersion.sqrt()
 }
 
-/// Дифференциальной функция распределения
-fn distribution_density(vec: &[u32]) -> [f32; 100] {
-    let mut result = [0.0; 100];
-    // при встрече числа обновляем его вероятность на 1/100 и записываем в результирующий массив
-    for num in vec {
-        result[*num as usize] += 1.0 / vec.len() as f32;
-    }
+/// Центральный момент порядка `order`
+fn central_moment(sample: &[f64], expectation: f64, order: i32) -> f64 {
+    sample
+        .iter()
+        .map(|x| (x - expectation).powi(order))
+        .sum::<f64>()
+        / sample.len() as f64
+}
 
-    result
+/// Коэффициент асимметрии: третий центральный момент, деленный на куб среднеквадратичного отклонения
+fn skewness(sample: &[f64], expectation: f64, dispersion: f64) -> f64 {
+    central_moment(sample, expectation, 3) / dispersion.powf(1.5)
 }
 
-/// Интегральная функция распределения
-fn distribution(density: &[f32]) -> [f32; 100] {
-    let mut result = [0.0; 100];
-    let mut sum = 0.0;
-    for (idx, prob) in density.iter().enumerate() {
-        // сумма вероятности растет для каждого элемета
-        sum += prob;
-        result[idx] = sum;
-    }
+/// Коэффициент эксцесса: четвертый центральный момент, деленный на квадрат дисперсии, минус 3
+fn kurtosis(sample: &[f64], expectation: f64, dispersion: f64) -> f64 {
+    central_moment(sample, expectation, 4) / dispersion.powi(2) - 3.
+}
 
-    result
+/// Доверительный интервал для мат. ожидания с доверительной вероятностью `level`
+/// по несмещенной выборочной дисперсии.
+/// Использует нормальное приближение, поэтому точен для больших выборок (от нескольких десятков значений)
+pub(crate) fn confidence_interval(
+    size: usize,
+    expectation: f64,
+    sample_dispersion: f64,
+    level: f64,
+) -> ConfidenceInterval {
+    let z = special::normal_quantile(0.5 + level / 2.);
+    let half_width = z * (sample_dispersion / size as f64).sqrt();
+    ConfidenceInterval {
+        level,
+        low: expectation - half_width,
+        high: expectation + half_width,
+    }
 }
 
 /// Проверяем принадледит ли точка сектору
@@ -86,10 +326,156 @@ pub fn check((x, y): (f32, f32)) -> bool {
 /// Расчитываем какова вероятность попадания случайной величины в сектор
 pub fn get_probability(x: &[f32], y: &[f32]) -> f32 {
     // Создаем из 2х массивов массив точек с координатами (x, y)
-    let points = x.into_iter().zip(y.into_iter());
+    let points = x.iter().zip(y);
     // находим только точки лежащие в секторе
     let count = points.filter(|(x, y)| check((**x, **y))).count();
 
     // считаем вероятность
     count as f32 / x.len() as f32
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Наборы NumAcc1-NumAcc4 из NIST StRD: большое мат. ожидание при маленьком разбросе.
+    /// Первое значение `middle`, затем `pairs` пар `middle - step`, `middle + step`
+    pub(crate) fn num_acc(middle: f64, step: f64, pairs: usize) -> Vec<f64> {
+        let mut sample = vec![middle];
+        for _ in 0..pairs {
+            sample.push(middle - step);
+            sample.push(middle + step);
+        }
+        sample
+    }
+
+    /// Проверяет, что относительная ошибка не больше `tolerance`
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let error = ((actual - expected) / expected).abs();
+        assert!(
+            error <= tolerance,
+            "expected {}, got {} (relative error {})",
+            expected,
+            actual,
+            error
+        );
+    }
+
+    #[test]
+    fn num_acc1() {
+        let stats = stats(&[10000001., 10000003., 10000002.], &StatsOptions::default()).unwrap();
+        assert_close(stats.expectation, 10000002., 1e-15);
+        assert_close(stats.sample_deviation, 1., 1e-12);
+    }
+
+    #[test]
+    fn num_acc2() {
+        let stats = stats(&num_acc(1.2, 0.1, 500), &StatsOptions::default()).unwrap();
+        assert_close(stats.expectation, 1.2, 1e-14);
+        assert_close(stats.sample_deviation, 0.1, 1e-12);
+    }
+
+    #[test]
+    fn num_acc3() {
+        let stats = stats(&num_acc(1000000.2, 0.1, 500), &StatsOptions::default()).unwrap();
+        assert_close(stats.expectation, 1000000.2, 1e-14);
+        assert_close(stats.sample_deviation, 0.1, 1e-8);
+    }
+
+    #[test]
+    fn num_acc4() {
+        let stats = stats(&num_acc(10000000.2, 0.1, 500), &StatsOptions::default()).unwrap();
+        assert_close(stats.expectation, 10000000.2, 1e-14);
+        assert_close(stats.sample_deviation, 0.1, 1e-7);
+    }
+
+    #[test]
+    fn population_and_sample_dispersion() {
+        let sample = (1..=10).collect::<Vec<u32>>();
+        let stats = stats(&sample, &StatsOptions::default()).unwrap();
+        assert_close(stats.expectation, 5.5, 1e-15);
+        assert_close(stats.dispersion, 8.25, 1e-15);
+        assert_close(stats.sample_dispersion, 82.5 / 9., 1e-15);
+        assert!(stats.skewness.abs() < 1e-12);
+        assert_close(stats.kurtosis, -1.2242424242424244, 1e-12);
+    }
+
+    #[test]
+    fn expectation_does_not_overflow() {
+        let sample = vec![u32::MAX; 1000];
+        assert_eq!(expectation(&sample), u32::MAX as f64);
+    }
+
+    /// Постоянная выборка и выборка со значениями не меньше 100 не ломают гистограмму
+    #[test]
+    fn any_sample_range() {
+        let constant = stats(&[42_i64; 50], &StatsOptions::default()).unwrap();
+        assert_eq!(constant.edges, vec![42., 43.]);
+        assert_eq!(constant.density, vec![1.]);
+
+        let options = StatsOptions {
+            binning: Binning::Sturges,
+            ..StatsOptions::default()
+        };
+        let large = stats(&[150_u32, 250, 999, 100], &options).unwrap();
+        assert_eq!(large.edges.first(), Some(&100.));
+        assert_eq!(large.edges.last(), Some(&1000.));
+        assert!((large.distribution.last().unwrap() - 1.).abs() < 1e-12);
+
+        let options = StatsOptions {
+            range: Some((1., 1.)),
+            ..StatsOptions::default()
+        };
+        assert!(stats(&[1., 2.], &options).is_err());
+    }
+
+    /// Медиана и квантили линейно интерполируются между соседними значениями отсортированной выборки
+    #[test]
+    fn quantile_interpolation() {
+        let options = StatsOptions {
+            quantiles: vec![0., 0.25, 0.75, 1.],
+            ..StatsOptions::default()
+        };
+        let stats = stats(&[4_u32, 1, 3, 2], &options).unwrap();
+        assert_close(stats.median, 2.5, 1e-15);
+        let values = stats.quantiles.iter().map(|q| q.value).collect::<Vec<_>>();
+        assert_eq!(values, vec![1., 1.75, 3.25, 4.]);
+        assert_eq!((stats.min, stats.max), (1., 4.));
+
+        let odd = super::stats(&[5., 1., 3.], &StatsOptions::default()).unwrap();
+        assert_eq!(odd.median, 3.);
+    }
+
+    /// Смещенные оценки асимметрии и эксцесса, как scipy.stats.skew и scipy.stats.kurtosis
+    #[test]
+    fn skewness_and_kurtosis() {
+        let stats = stats(&[2_u32, 8, 0, 4, 1, 9, 9, 0], &StatsOptions::default()).unwrap();
+        assert_close(stats.skewness, 0.2650554122698573, 1e-12);
+        assert_close(stats.kurtosis, -1.6660010752838508, 1e-12);
+
+        let heavy_tail = super::stats(
+            &[1., 2., 3., 4., 5., 6., 7., 8., 9., 100.],
+            &StatsOptions::default(),
+        )
+        .unwrap();
+        assert_close(heavy_tail.skewness, 2.6300838238836732, 1e-12);
+        assert_close(heavy_tail.kurtosis, 4.997552566718839, 1e-12);
+    }
+
+    /// Полуширина интервала равна z * sqrt(s^2 / n), z = 1.959963984540054 для 0.95.
+    /// Квантиль считается аппроксимацией Акклама, поэтому допуск 1.15e-9
+    #[test]
+    fn confidence_interval_width() {
+        let interval = confidence_interval(100, 10., 4., 0.95);
+        assert_eq!(interval.level, 0.95);
+        assert_close(10. - interval.low, 1.959963984540054 * 0.2, 1.15e-9);
+        assert_close(interval.high - 10., 1.959963984540054 * 0.2, 1.15e-9);
+
+        let sample = (1..=10).collect::<Vec<u32>>();
+        let stats = stats(&sample, &StatsOptions::default()).unwrap();
+        let interval = stats.confidence_interval;
+        let width = 2. * 1.959963984540054 * (82.5 / 9. / 10_f64).sqrt();
+        assert_close(interval.high - interval.low, width, 1.15e-9);
+        assert_close((interval.low + interval.high) / 2., 5.5, 1e-15);
+    }
+}
@@ -0,0 +1,344 @@
+//! Метод Монте-Карло: оценка числа π и численное интегрирование по случайным точкам.
+//!
+//! Для π доля случайных точек единичного квадрата, попавших в четверть круга, стремится к π / 4.
+//! Интеграл по N-мерному прямоугольному параллелепипеду оценивается методом "попал - не попал"
+//! или методом среднего значения функции.
+
+use crate::generator::Generator;
+use crate::ConfidenceInterval;
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::str::FromStr;
+
+/// Настройки оценки π
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct PiOptions {
+    /// Количество контрольных точек на кривой сходимости
+    pub checkpoints: usize,
+    /// Доверительная вероятность для интервала оценки, например 0.95
+    pub confidence_level: f64,
+}
+
+impl Default for PiOptions {
+    fn default() -> Self {
+        PiOptions {
+            checkpoints: 20,
+            confidence_level: 0.95,
+        }
+    }
+}
+
+/// Оценка π после заданного количества точек
+#[derive(Serialize, Deserialize, Copy, Clone, Debug)]
+pub struct PiCheckpoint {
+    /// Количество точек
+    pub size: usize,
+    /// Оценка π
+    pub pi: f64,
+    /// Абсолютная ошибка |оценка - π|
+    pub error: f64,
+    /// Стандартная ошибка оценки
+    pub standard_error: f64,
+}
+
+impl PiCheckpoint {
+    fn new(size: usize, hits: usize) -> Self {
+        let p = hits as f64 / size as f64;
+        PiCheckpoint {
+            size,
+            pi: 4. * p,
+            error: (4. * p - PI).abs(),
+            // попадание в четверть круга - испытание Бернулли с вероятностью p
+            standard_error: 4. * (p * (1. - p) / size as f64).sqrt(),
+        }
+    }
+}
+
+/// Результат оценки π
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PiEstimate {
+    /// Начальное значение генератора, с которым получен результат
+    pub seed: Option<u64>,
+    /// Количество точек
+    pub size: usize,
+    /// Количество точек, попавших в четверть круга
+    pub hits: usize,
+    /// Оценка π
+    pub pi: f64,
+    /// Абсолютная ошибка |оценка - π|
+    pub error: f64,
+    /// Стандартная ошибка оценки
+    pub standard_error: f64,
+    /// Доверительный интервал для π
+    pub confidence_interval: ConfidenceInterval,
+    /// Кривая сходимости: оценка π на контрольных точках
+    pub checkpoints: Vec<PiCheckpoint>,
+}
+
+impl_with_seed!(PiEstimate);
+
+/// Оценивает π по `size` случайным точкам единичного квадрата, которые выдает `next_point`
+pub fn estimate_pi<F>(mut next_point: F, size: usize, options: &PiOptions) -> PiEstimate
+where
+    F: FnMut() -> (f64, f64),
+{
+    assert!(size > 0, "size should be positive");
+
+    let sizes = checkpoint_sizes(size, options.checkpoints);
+    let mut checkpoints = Vec::with_capacity(sizes.len());
+    let mut hits = 0;
+
+    for n in 1..=size {
+        let (x, y) = next_point();
+        if x * x + y * y <= 1. {
+            hits += 1;
+        }
+
+        if sizes.get(checkpoints.len()) == Some(&n) {
+            checkpoints.push(PiCheckpoint::new(n, hits));
+        }
+    }
+
+    let PiCheckpoint {
+        pi,
+        error,
+        standard_error,
+        ..
+    } = PiCheckpoint::new(size, hits);
+    let z = crate::special::normal_quantile(0.5 + options.confidence_level / 2.);
+
+    PiEstimate {
+        seed: None,
+        size,
+        hits,
+        pi,
+        error,
+        standard_error,
+        confidence_interval: ConfidenceInterval {
+            level: options.confidence_level,
+            low: pi - z * standard_error,
+            high: pi + z * standard_error,
+        },
+        checkpoints,
+    }
+}
+
+/// Размеры выборки для контрольных точек: равномерно в логарифмическом масштабе, последняя - `size`
+fn checkpoint_sizes(size: usize, count: usize) -> Vec<usize> {
+    let mut sizes = (1..=count)
+        .map(|i| (size as f64).powf(i as f64 / count as f64).round() as usize)
+        .map(|n| n.clamp(1, size))
+        .collect::<Vec<_>>();
+    sizes.dedup();
+    sizes
+}
+
+/// Метод интегрирования
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum IntegrationMethod {
+    /// Доля случайных точек под графиком функции
+    #[serde(rename = "hitOrMiss")]
+    HitOrMiss,
+    /// Среднее значение функции в случайных точках, умноженное на объем области
+    #[serde(rename = "sampleMean")]
+    SampleMean,
+}
+
+impl FromStr for IntegrationMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hitOrMiss" => Ok(IntegrationMethod::HitOrMiss),
+            "sampleMean" => Ok(IntegrationMethod::SampleMean),
+            _ => Err(anyhow!(
+                "Unknown integration method {:?}, expected hitOrMiss or sampleMean",
+                s
+            )),
+        }
+    }
+}
+
+/// Результат интегрирования методом Монте-Карло
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Integral {
+    /// Начальное значение генератора, с которым получен результат
+    pub seed: Option<u64>,
+    /// Метод интегрирования
+    pub method: IntegrationMethod,
+    /// Размерность области интегрирования
+    pub dimensions: usize,
+    /// Количество случайных точек
+    pub size: usize,
+    /// Оценка интеграла
+    pub value: f64,
+    /// Дисперсия оценки интеграла
+    pub variance: f64,
+    /// Стандартная ошибка оценки, корень из дисперсии
+    pub standard_error: f64,
+    /// Доверительный интервал для интеграла
+    pub confidence_interval: ConfidenceInterval,
+}
+
+impl Integral {
+    fn new(
+        method: IntegrationMethod,
+        domain: &[(f64, f64)],
+        size: usize,
+        value: f64,
+        variance: f64,
+        confidence_level: f64,
+    ) -> Self {
+        let standard_error = variance.sqrt();
+        let z = crate::special::normal_quantile(0.5 + confidence_level / 2.);
+
+        Integral {
+            seed: None,
+            method,
+            dimensions: domain.len(),
+            size,
+            value,
+            variance,
+            standard_error,
+            confidence_interval: ConfidenceInterval {
+                level: confidence_level,
+                low: value - z * standard_error,
+                high: value + z * standard_error,
+            },
+        }
+    }
+}
+
+impl_with_seed!(Integral);
+
+/// Интегрирует `f` по области `domain` (отрезок [low, high] на каждое измерение) методом среднего значения:
+/// интеграл равен объему области, умноженному на среднее значение функции в `size` случайных точках
+pub fn sample_mean<F>(
+    rng: &mut dyn Generator,
+    f: F,
+    domain: &[(f64, f64)],
+    size: usize,
+    confidence_level: f64,
+) -> Integral
+where
+    F: Fn(&[f64]) -> f64,
+{
+    assert!(size > 0, "size should be positive");
+
+    let volume = volume(domain);
+    let mut point = vec![0.; domain.len()];
+
+    // среднее и сумма квадратов отклонений значений функции по алгоритму Уэлфорда
+    let (mut mean, mut m2) = (0., 0.);
+    for n in 1..=size {
+        random_point(rng, domain, &mut point);
+        let value = f(&point);
+
+        let delta = value - mean;
+        mean += delta / n as f64;
+        m2 += delta * (value - mean);
+    }
+
+    // по одной точке разброс оценить нельзя
+    let sample_dispersion = if size < 2 {
+        f64::NAN
+    } else {
+        m2 / (size - 1) as f64
+    };
+    Integral::new(
+        IntegrationMethod::SampleMean,
+        domain,
+        size,
+        volume * mean,
+        volume * volume * sample_dispersion / size as f64,
+        confidence_level,
+    )
+}
+
+/// Интегрирует `f` по области `domain` методом "попал - не попал". Значения функции должны лежать
+/// в `bounds` = [low, high]: в параллелепипеде область x [low, high] бросаются случайные точки,
+/// интеграл равен low * V + доля точек под графиком * объем параллелепипеда (high - low) * V
+pub fn hit_or_miss<F>(
+    rng: &mut dyn Generator,
+    f: F,
+    domain: &[(f64, f64)],
+    (low, high): (f64, f64),
+    size: usize,
+    confidence_level: f64,
+) -> Integral
+where
+    F: Fn(&[f64]) -> f64,
+{
+    assert!(size > 0, "size should be positive");
+    assert!(low < high, "low should be less than high");
+
+    let volume = volume(domain);
+    let mut point = vec![0.; domain.len()];
+
+    let mut hits = 0;
+    for _ in 0..size {
+        random_point(rng, domain, &mut point);
+        let y = low + (high - low) * rng.next_f64();
+        if y < f(&point) {
+            hits += 1;
+        }
+    }
+
+    // попадание под график - испытание Бернулли с вероятностью p
+    let p = hits as f64 / size as f64;
+    let box_volume = volume * (high - low);
+    Integral::new(
+        IntegrationMethod::HitOrMiss,
+        domain,
+        size,
+        low * volume + box_volume * p,
+        box_volume * box_volume * p * (1. - p) / size as f64,
+        confidence_level,
+    )
+}
+
+/// Объем прямоугольного параллелепипеда
+fn volume(domain: &[(f64, f64)]) -> f64 {
+    domain.iter().map(|(low, high)| high - low).product()
+}
+
+/// Заполняет `point` координатами случайной точки области
+fn random_point(rng: &mut dyn Generator, domain: &[(f64, f64)], point: &mut [f64]) {
+    for (x, (low, high)) in point.iter_mut().zip(domain) {
+        *x = low + (high - low) * rng.next_f64();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::RustRng;
+
+    /// Интеграл x * y по [0, 1] x [0, 2] равен 1
+    #[test]
+    fn both_methods_converge() {
+        let domain = [(0., 1.), (0., 2.)];
+        let f = |point: &[f64]| point[0] * point[1];
+
+        let mean = sample_mean(&mut RustRng::from_seed(1), f, &domain, 100_000, 0.95);
+        let hits = hit_or_miss(
+            &mut RustRng::from_seed(1),
+            f,
+            &domain,
+            (0., 2.),
+            100_000,
+            0.95,
+        );
+
+        for integral in [mean, hits].iter() {
+            assert_eq!(integral.dimensions, 2);
+            assert!(
+                (integral.value - 1.).abs() < 4. * integral.standard_error,
+                "{:?}",
+                integral
+            );
+        }
+    }
+}
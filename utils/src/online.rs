@@ -0,0 +1,236 @@
+//! Потоковый расчет статистики: значения обрабатываются по одному или порциями,
+//! вся выборка в памяти не хранится. Накопители можно считать в разных потоках и объединять
+
+use crate::criteria::{self, ChiSquared};
+use crate::histogram::Histogram;
+use crate::{ConfidenceInterval, Sample};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Накопитель статистики: количество, мат. ожидание и центральные моменты по алгоритму Уэлфорда,
+/// минимум, максимум и гистограмма на заданном диапазоне
+#[derive(Clone, Debug)]
+pub struct OnlineStats {
+    /// Количество обработанных значений
+    size: usize,
+    /// Текущее мат. ожидание
+    mean: f64,
+    /// Суммы степеней отклонений от мат. ожидания: второй, третьей и четвертой
+    m2: f64,
+    m3: f64,
+    m4: f64,
+    min: f64,
+    max: f64,
+    histogram: Histogram,
+}
+
+impl OnlineStats {
+    /// Создает пустой накопитель. Диапазон гистограммы нужно знать заранее,
+    /// для проверки равномерности это диапазон генератора. Вернет ошибку, если диапазон пуст или не конечен
+    pub fn new(range: (f64, f64), bins: usize, discrete: bool) -> Result<Self> {
+        Ok(OnlineStats {
+            size: 0,
+            mean: 0.,
+            m2: 0.,
+            m3: 0.,
+            m4: 0.,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            histogram: Histogram::empty(range, bins, discrete)?,
+        })
+    }
+
+    /// Количество обработанных значений
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Добавляет одно значение
+    pub fn push<T: Sample>(&mut self, x: T) {
+        let x = x.to_f64();
+
+        let n1 = self.size as f64;
+        self.size += 1;
+        let n = self.size as f64;
+
+        let delta = x - self.mean;
+        let delta_n = delta / n;
+        let delta_n2 = delta_n * delta_n;
+        let term = delta * delta_n * n1;
+
+        // порядок важен: старшие моменты обновляются через еще не обновленные младшие
+        self.mean += delta_n;
+        self.m4 += term * delta_n2 * (n * n - 3. * n + 3.) + 6. * delta_n2 * self.m2
+            - 4. * delta_n * self.m3;
+        self.m3 += term * delta_n * (n - 2.) - 3. * delta_n * self.m2;
+        self.m2 += term;
+
+        self.min = self.min.min(x);
+        self.max = self.max.max(x);
+        self.histogram.push(x);
+    }
+
+    /// Добавляет порцию значений
+    pub fn extend<T: Sample>(&mut self, chunk: &[T]) {
+        for x in chunk {
+            self.push(*x);
+        }
+    }
+
+    /// Объединяет с накопителем, посчитанным по другой части выборки (например, в другом потоке).
+    /// Гистограммы обоих накопителей должны иметь одинаковые интервалы
+    pub fn merge(&mut self, other: &OnlineStats) {
+        if other.size == 0 {
+            return;
+        }
+        if self.size == 0 {
+            *self = other.clone();
+            return;
+        }
+
+        let (na, nb) = (self.size as f64, other.size as f64);
+        let n = na + nb;
+        let delta = other.mean - self.mean;
+        let delta2 = delta * delta;
+
+        let m2 = self.m2 + other.m2 + delta2 * na * nb / n;
+        let m3 = self.m3
+            + other.m3
+            + delta2 * delta * na * nb * (na - nb) / (n * n)
+            + 3. * delta * (na * other.m2 - nb * self.m2) / n;
+        let m4 = self.m4
+            + other.m4
+            + delta2 * delta2 * na * nb * (na * na - na * nb + nb * nb) / (n * n * n)
+            + 6. * delta2 * (na * na * other.m2 + nb * nb * self.m2) / (n * n)
+            + 4. * delta * (na * other.m3 - nb * self.m3) / n;
+
+        self.mean += delta * nb / n;
+        self.m2 = m2;
+        self.m3 = m3;
+        self.m4 = m4;
+        self.size += other.size;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.histogram.merge(&other.histogram);
+    }
+
+    /// Итоговая статистика по всем обработанным значениям,
+    /// `confidence_level` - доверительная вероятность для интервала мат. ожидания
+    pub fn summary(&self, confidence_level: f64) -> OnlineSummary {
+        let n = self.size as f64;
+        let dispersion = self.m2 / n;
+        let sample_dispersion = if self.size < 2 {
+            f64::NAN
+        } else {
+            self.m2 / (n - 1.)
+        };
+
+        OnlineSummary {
+            seed: None,
+            size: self.size,
+            expectation: self.mean,
+            dispersion,
+            sample_dispersion,
+            deviation: dispersion.sqrt(),
+            sample_deviation: sample_dispersion.sqrt(),
+            skewness: self.m3 / n / dispersion.powf(1.5),
+            kurtosis: self.m4 / n / dispersion.powi(2) - 3.,
+            min: self.min,
+            max: self.max,
+            confidence_interval: crate::confidence_interval(
+                self.size,
+                self.mean,
+                sample_dispersion,
+                confidence_level,
+            ),
+            edges: self.histogram.edges.clone(),
+            density: self.histogram.density(),
+            distribution: self.histogram.distribution(),
+            chi_squared: criteria::chi_squared_uniform(&self.histogram),
+        }
+    }
+}
+
+/// Статистика, посчитанная потоковым способом. В отличие от [crate::Stats] не содержит квантилей
+/// и автокорреляции: для них нужна вся выборка
+#[derive(Serialize, Deserialize)]
+pub struct OnlineSummary {
+    /// Начальное значение генератора, с которым получена выборка
+    seed: Option<u64>,
+    /// Размер выборки
+    size: usize,
+    /// Мат ожидание
+    expectation: f64,
+    /// Дисперсия генеральной совокупности (деленная на n)
+    dispersion: f64,
+    /// Несмещенная выборочная дисперсия (деленная на n - 1)
+    sample_dispersion: f64,
+    /// Среднеквардатичное отклонение
+    deviation: f64,
+    /// Выборочное среднеквардатичное отклонение
+    sample_deviation: f64,
+    /// Коэффициент асимметрии
+    skewness: f64,
+    /// Коэффициент эксцесса (для нормального закона равен 0)
+    kurtosis: f64,
+    /// Минимальное значение
+    min: f64,
+    /// Максимальное значение
+    max: f64,
+    /// Доверительный интервал для мат. ожидания
+    confidence_interval: ConfidenceInterval,
+    /// Границы интервалов гистограммы, i-й интервал - [edges[i], edges[i+1])
+    edges: Vec<f64>,
+    /// Частота попадания в каждый интервал
+    density: Vec<f64>,
+    /// Интегральаня функция распределения на правой границе каждого интервала
+    distribution: Vec<f64>,
+    /// Проверка равномерности по критерию хи-квадрат
+    chi_squared: ChiSquared,
+}
+
+impl_with_seed!(OnlineSummary);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::num_acc;
+
+    /// Набор NumAcc4 из NIST StRD: мат. ожидание 10000000.2, выборочное отклонение 0.1
+    #[test]
+    fn num_acc4_single_pass() {
+        let mut stats = OnlineStats::new((0., 1.), 10, false).unwrap();
+        stats.extend(&num_acc(10000000.2, 0.1, 500));
+        let summary = stats.summary(0.95);
+
+        assert!((summary.expectation - 10000000.2).abs() < 1e-7);
+        assert!((summary.sample_deviation - 0.1).abs() < 1e-8);
+    }
+
+    #[test]
+    fn merge_matches_single_pass() {
+        let sample = (0..1000)
+            .map(|x| ((x * 37) % 101) as f64)
+            .collect::<Vec<_>>();
+
+        let mut whole = OnlineStats::new((0., 101.), 10, true).unwrap();
+        whole.extend(&sample);
+
+        let mut merged = OnlineStats::new((0., 101.), 10, true).unwrap();
+        for chunk in sample.chunks(77) {
+            let mut part = OnlineStats::new((0., 101.), 10, true).unwrap();
+            part.extend(chunk);
+            merged.merge(&part);
+        }
+
+        let (whole, merged) = (whole.summary(0.95), merged.summary(0.95));
+        assert_eq!(whole.size, merged.size);
+        assert!((whole.expectation - merged.expectation).abs() < 1e-10);
+        assert!((whole.dispersion - merged.dispersion).abs() < 1e-8);
+        assert!((whole.skewness - merged.skewness).abs() < 1e-8);
+        assert!((whole.kurtosis - merged.kurtosis).abs() < 1e-8);
+        assert_eq!(whole.min, merged.min);
+        assert_eq!(whole.max, merged.max);
+        assert_eq!(whole.density, merged.density);
+    }
+}
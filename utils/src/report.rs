@@ -0,0 +1,87 @@
+//! Вывод результатов в текстовом виде для запуска без графического интерфейса
+
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Формат вывода результатов
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    /// JSON, такой же как отправляется в графический интерфейс
+    Json,
+    /// CSV из двух колонок: поле и значение
+    Csv,
+    /// Выровненная таблица для чтения человеком
+    Table,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "table" => Ok(Format::Table),
+            _ => Err(anyhow!(
+                "Unknown format {:?}, expected json, csv or table",
+                s
+            )),
+        }
+    }
+}
+
+/// Преобразует результат в строку указанного формата.
+/// Вложенные структуры и массивы разворачиваются в поля вида `chi_squared.p_value` и `density[3]`
+pub fn render<T: Serialize>(value: &T, format: Format) -> Result<String> {
+    if format == Format::Json {
+        return Ok(serde_json::to_string_pretty(value)? + "\n");
+    }
+
+    let mut rows = Vec::new();
+    flatten(&serde_json::to_value(value)?, String::new(), &mut rows);
+
+    let result = match format {
+        Format::Csv => {
+            let mut out = String::from("field,value\n");
+            for (field, value) in rows {
+                out.push_str(&format!("{},{}\n", field, value));
+            }
+            out
+        }
+        _ => {
+            let width = rows.iter().map(|(field, _)| field.len()).max().unwrap_or(0);
+            let mut out = String::new();
+            for (field, value) in rows {
+                out.push_str(&format!("{:width$}  {}\n", field, value, width = width));
+            }
+            out
+        }
+    };
+
+    Ok(result)
+}
+
+/// Разворачивает json в список пар (поле, значение)
+fn flatten(value: &Value, prefix: String, rows: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let field = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(value, field, rows);
+            }
+        }
+        Value::Array(arr) => {
+            for (idx, value) in arr.iter().enumerate() {
+                flatten(value, format!("{}[{}]", prefix, idx), rows);
+            }
+        }
+        Value::String(str) => rows.push((prefix, str.clone())),
+        _ => rows.push((prefix, value.to_string())),
+    }
+}
@@ -0,0 +1,289 @@
+//! Специальные функции, необходимые для расчета p-значений статистических критериев
+
+/// Коэффициенты аппроксимации Ланцоша (g = 7, n = 9)
+const LANCZOS: [f64; 9] = [
+    0.999_999_999_999_809_9,
+    676.520_368_121_885_1,
+    -1_259.139_216_722_402_8,
+    771.323_428_777_653_1,
+    -176.615_029_162_140_6,
+    12.507_343_278_686_905,
+    -0.138_571_095_265_720_12,
+    9.984_369_578_019_572e-6,
+    1.505_632_735_149_311_6e-7,
+];
+
+/// Точность вычисления рядов и цепных дробей
+const EPS: f64 = 1e-14;
+/// Максимальное количество итераций при вычислении рядов и цепных дробей
+const MAX_ITER: usize = 1000;
+
+/// Натуральный логарифм гамма-функции для x > 0
+pub fn ln_gamma(x: f64) -> f64 {
+    if x < 0.5 {
+        // формула отражения
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1. - x);
+    }
+
+    let x = x - 1.;
+    let t = x + 7.5;
+    let sum = LANCZOS[1..]
+        .iter()
+        .enumerate()
+        .fold(LANCZOS[0], |sum, (i, c)| sum + c / (x + i as f64 + 1.));
+
+    0.5 * (2. * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+/// Регуляризованная нижняя неполная гамма-функция P(a, x)
+pub fn gamma_p(a: f64, x: f64) -> f64 {
+    if x <= 0. {
+        0.
+    } else if x < a + 1. {
+        gamma_series(a, x)
+    } else {
+        1. - gamma_continued_fraction(a, x)
+    }
+}
+
+/// Регуляризованная верхняя неполная гамма-функция Q(a, x) = 1 - P(a, x)
+pub fn gamma_q(a: f64, x: f64) -> f64 {
+    if x <= 0. {
+        1.
+    } else if x < a + 1. {
+        1. - gamma_series(a, x)
+    } else {
+        gamma_continued_fraction(a, x)
+    }
+}
+
+/// P(a, x) в виде ряда, быстро сходится при x < a + 1
+fn gamma_series(a: f64, x: f64) -> f64 {
+    let mut term = 1. / a;
+    let mut sum = term;
+    let mut ap = a;
+    for _ in 0..MAX_ITER {
+        ap += 1.;
+        term *= x / ap;
+        sum += term;
+        if term.abs() < sum.abs() * EPS {
+            break;
+        }
+    }
+
+    sum * (-x + a * x.ln() - ln_gamma(a)).exp()
+}
+
+/// Q(a, x) в виде цепной дроби (метод Лентца), быстро сходится при x > a + 1
+fn gamma_continued_fraction(a: f64, x: f64) -> f64 {
+    let tiny = f64::MIN_POSITIVE / EPS;
+    let mut b = x + 1. - a;
+    let mut c = 1. / tiny;
+    let mut d = 1. / b;
+    let mut h = d;
+    for i in 1..MAX_ITER {
+        let an = -(i as f64) * (i as f64 - a);
+        b += 2.;
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+        d = 1. / d;
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.).abs() < EPS {
+            break;
+        }
+    }
+
+    (-x + a * x.ln() - ln_gamma(a)).exp() * h
+}
+
+/// Вероятность того, что случайная величина с распределением хи-квадрат
+/// с `degrees_of_freedom` степенями свободы превысит `statistic`
+pub fn chi_squared_sf(statistic: f64, degrees_of_freedom: usize) -> f64 {
+    gamma_q(degrees_of_freedom as f64 / 2., statistic / 2.)
+}
+
+/// Функция ошибок erf(x), выражается через неполную гамма-функцию: erf(x) = P(1/2, x^2)
+pub fn erf(x: f64) -> f64 {
+    let value = gamma_p(0.5, x * x);
+    if x < 0. {
+        -value
+    } else {
+        value
+    }
+}
+
+/// Функция распределения стандартного нормального закона
+pub fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1. + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Вероятность того, что величина с распределением Колмогорова превысит `lambda`:
+/// Q(λ) = 2 Σ (-1)^(k-1) exp(-2 k^2 λ^2)
+pub fn kolmogorov_sf(lambda: f64) -> f64 {
+    if lambda < 0.2 {
+        // ряд сходится плохо, но значение практически равно 1
+        return 1.;
+    }
+
+    let mut sum = 0.;
+    let mut sign = 1.;
+    for k in 1..=100 {
+        let term = (-2. * (k * k) as f64 * lambda * lambda).exp();
+        sum += sign * term;
+        if term < EPS * sum.abs() {
+            break;
+        }
+        sign = -sign;
+    }
+
+    (2. * sum).clamp(0., 1.)
+}
+
+/// Квантиль стандартного нормального закона (обратная функция распределения) для 0 < p < 1.
+/// Рациональная аппроксимация Акклама, относительная погрешность не больше 1.15e-9
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const P_LOW: f64 = 0.02425;
+
+    if p <= 0. {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1. {
+        return f64::INFINITY;
+    }
+
+    if p < P_LOW {
+        // нижний хвост
+        let q = (-2. * p.ln()).sqrt();
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.)
+    } else if p <= 1. - P_LOW {
+        // центральная часть
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.)
+    } else {
+        // верхний хвост симметричен нижнему
+        -normal_quantile(1. - p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ln_gamma_known_values() {
+        // Γ(1/2) = √π, Γ(1/4) считается через формулу отражения, Γ(10) = 9!
+        assert!((ln_gamma(0.5) - std::f64::consts::PI.sqrt().ln()).abs() < 1e-12);
+        assert!((ln_gamma(0.25) - 1.288_022_524_698_077).abs() < 1e-12);
+        assert!(ln_gamma(1.).abs() < 1e-12);
+        assert!((ln_gamma(10.) - 362_880_f64.ln()).abs() < 1e-10);
+    }
+
+    /// Для целого a: Q(a, x) = e^-x Σ x^k / k! по k < a
+    #[test]
+    fn incomplete_gamma_both_branches() {
+        // x < a + 1 - ряд
+        let q = 5. * (-2_f64).exp();
+        assert!((gamma_q(3., 2.) - q).abs() < 1e-12);
+        assert!((gamma_p(3., 2.) - (1. - q)).abs() < 1e-12);
+
+        // x > a + 1 - цепная дробь
+        let q = 61. * (-10_f64).exp();
+        assert!((gamma_q(3., 10.) - q).abs() < 1e-14);
+        assert!((gamma_p(3., 10.) - (1. - q)).abs() < 1e-12);
+
+        assert_eq!(gamma_p(3., 0.), 0.);
+        assert_eq!(gamma_q(3., 0.), 1.);
+    }
+
+    #[test]
+    fn chi_squared_critical_values() {
+        // критические значения уровня 0.05 для 1 и 10 степеней свободы
+        assert!((chi_squared_sf(3.841_458_820_694_124, 1) - 0.05).abs() < 1e-9);
+        assert!((chi_squared_sf(18.307_038_053_275_146, 10) - 0.05).abs() < 1e-9);
+        // для 2 степеней свободы хвост равен e^(-x/2)
+        assert!((chi_squared_sf(3., 2) - (-1.5_f64).exp()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn erf_known_values() {
+        assert!((erf(0.5) - 0.520_499_877_813_046_5).abs() < 1e-12);
+        assert!((erf(1.) - 0.842_700_792_949_714_9).abs() < 1e-12);
+        assert!((erf(-2.) + 0.995_322_265_018_952_7).abs() < 1e-12);
+        assert_eq!(erf(0.), 0.);
+
+        assert!((normal_cdf(0.) - 0.5).abs() < 1e-15);
+        assert!((normal_cdf(1.959_963_984_540_054) - 0.975).abs() < 1e-12);
+    }
+
+    /// Проверяются все три участка аппроксимации Акклама: нижний хвост, центр и верхний хвост
+    #[test]
+    fn normal_quantile_known_values() {
+        let known = [
+            (0.01, -2.326_347_874_040_841),
+            (0.5, 0.),
+            (0.975, 1.959_963_984_540_054),
+            (0.999, 3.090_232_306_167_813),
+        ];
+        for (p, quantile) in known.iter() {
+            let value = normal_quantile(*p);
+            assert!(
+                (value - quantile).abs() < 1.15e-9 * quantile.abs().max(1.),
+                "{}: {}",
+                p,
+                value
+            );
+        }
+
+        assert_eq!(normal_quantile(0.), f64::NEG_INFINITY);
+        assert_eq!(normal_quantile(1.), f64::INFINITY);
+    }
+
+    #[test]
+    fn kolmogorov_critical_values() {
+        // λ = 1.36 и 1.63 - критические значения уровней 0.05 и 0.01
+        assert!((kolmogorov_sf(1.36) - 0.05).abs() < 1e-3);
+        assert!((kolmogorov_sf(1.63) - 0.01).abs() < 1e-3);
+        assert_eq!(kolmogorov_sf(0.1), 1.);
+        assert!(kolmogorov_sf(5.) < 1e-20);
+    }
+}